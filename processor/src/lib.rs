@@ -9,7 +9,7 @@ use std::sync::{Arc, Condvar, Mutex};
 use std::{mem, thread};
 
 use scheduler::{
-    Pid, ProcessState, Scheduler, SchedulingDecision, StopReason, Syscall, SyscallResult,
+    EventSet, Pid, ProcessState, Scheduler, SchedulingDecision, StopReason, Syscall, SyscallResult,
 };
 
 /// Running iteration log
@@ -50,7 +50,7 @@ impl Display for Log {
         for pid in pids.into_iter() {
             writeln!(f, "{}", self.processes.get(pid).unwrap()).unwrap();
         }
-        if let Some(log) = self.stop_reason {
+        if let Some(log) = &self.stop_reason {
             writeln!(f, "{} -> {:?}", log.0, (log.1)).unwrap();
         }
         writeln!(f)
@@ -132,7 +132,7 @@ impl<S: Scheduler + 'static> Processor<S> {
     ///
     /// * `scheduler` - the scheduler to use for the simulation.
     /// * `f` - a function with the instructions for the process with
-    ///         PID 1.
+    ///   PID 1.
     ///
     /// ## Example
     ///
@@ -210,7 +210,7 @@ impl<S: Scheduler + 'static> Processor<S> {
             self.remaining.fetch_sub(1, Ordering::Relaxed);
             let mut scheduler = self.scheduler.lock().unwrap();
             reason.set_remaining(self.remaining.load(Ordering::Relaxed));
-            let result = scheduler.stop(reason);
+            let result = scheduler.stop(reason.clone());
             {
                 let mut logs = self.logs.lock().unwrap();
                 let len = logs.len();
@@ -249,6 +249,9 @@ impl<S: Scheduler + 'static> Processor<S> {
                     SchedulingDecision::Sleep(time) => {
                         println!("SLEEP {time}");
                     }
+                    SchedulingDecision::Idle(time) => {
+                        println!("IDLE {time}");
+                    }
                     SchedulingDecision::Deadlock => {
                         println!("DEADLOCK");
                         self.stop();
@@ -257,6 +260,14 @@ impl<S: Scheduler + 'static> Processor<S> {
                         println!("PANIC");
                         self.stop();
                     }
+                    SchedulingDecision::Starvation(pid) => {
+                        println!("STARVATION {pid}");
+                        self.stop();
+                    }
+                    SchedulingDecision::Stranded(pid) => {
+                        println!("STRANDED {pid}");
+                        self.stop();
+                    }
                     SchedulingDecision::Done => {
                         println!("DONE");
                         self.stop();
@@ -269,6 +280,13 @@ impl<S: Scheduler + 'static> Processor<S> {
         }
     }
 
+    fn take_wake_result(&self, pid: Pid) -> Option<SyscallResult> {
+        if !self.is_running() {
+            return None;
+        }
+        self.scheduler.lock().unwrap().take_wake_result(pid)
+    }
+
     fn get_logs(&self) -> Vec<Log> {
         let mut logs = self.logs.lock().unwrap();
         let mut res = vec![];
@@ -354,6 +372,22 @@ impl<S: Scheduler + 'static> Process<S> {
         self.suspend();
     }
 
+    /// Send a [`Syscall::WaitAny`] system call. Returns the event that woke
+    /// up the process, or `None` if the scheduler does not report it.
+    ///
+    /// * `events` - the event numbers to wait for.
+    pub fn wait_any(&self, events: &[usize]) -> Option<usize> {
+        println!("{}: WAIT ANY {:?}", self.pid, events);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::WaitAny(EventSet::new(events))));
+        self.suspend();
+        // The event is only known once the process is woken up
+        match self.processor.take_wake_result(self.pid) {
+            Some(SyscallResult::Event(event)) => Some(event),
+            _ => None,
+        }
+    }
+
     /// Send a [`Syscall::Signal`] system call.
     ///
     /// * `event` - the event number to signal.
@@ -364,6 +398,16 @@ impl<S: Scheduler + 'static> Process<S> {
         self.suspend();
     }
 
+    /// Send a [`Syscall::SignalOne`] system call.
+    ///
+    /// * `event` - the event number to signal.
+    pub fn signal_one(&self, event: usize) {
+        println!("{}: SIGNAL ONE {}", self.pid, event);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::SignalOne(event)));
+        self.suspend();
+    }
+
     /// Send a [`Syscall::Sleep`] system call.
     ///
     /// * `timeslice` - the amout of time to sleep.
@@ -374,6 +418,83 @@ impl<S: Scheduler + 'static> Process<S> {
         self.suspend();
     }
 
+    /// Send a [`Syscall::PreemptDisable`] system call.
+    pub fn preempt_disable(&self) {
+        println!("{}: PREEMPT DISABLE", self.pid);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::PreemptDisable));
+        self.suspend();
+    }
+
+    /// Send a [`Syscall::PreemptEnable`] system call.
+    pub fn preempt_enable(&self) {
+        println!("{}: PREEMPT ENABLE", self.pid);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::PreemptEnable));
+        self.suspend();
+    }
+
+    /// Send a [`Syscall::BlockIo`] system call.
+    ///
+    /// * `device` - the device number.
+    /// * `base_latency` - the time that the I/O request takes.
+    pub fn block_io(&self, device: usize, base_latency: usize) {
+        println!("{}: BLOCK IO {} {}", self.pid, device, base_latency);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::BlockIo {
+                device,
+                base_latency,
+            }));
+        self.suspend();
+    }
+
+    /// Send a [`Syscall::Donate`] system call.
+    ///
+    /// * `to` - the PID of the process that receives the priority.
+    /// * `amount` - the amount of priority to donate, 0 revokes the donation.
+    pub fn donate(&self, to: Pid, amount: i8) {
+        println!("{}: DONATE {} {}", self.pid, to, amount);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::Donate { to, amount }));
+        self.suspend();
+    }
+
+    /// Send a [`Syscall::AcquireLock`] system call.
+    pub fn acquire_lock(&self) {
+        println!("{}: ACQUIRE LOCK", self.pid);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::AcquireLock));
+        self.suspend();
+    }
+
+    /// Send a [`Syscall::ReleaseLock`] system call.
+    pub fn release_lock(&self) {
+        println!("{}: RELEASE LOCK", self.pid);
+        self.processor
+            .scheduler(StopReason::syscall(Syscall::ReleaseLock));
+        self.suspend();
+    }
+
+    /// Send a [`Syscall::Join`] system call and return the final timings
+    /// of the child, or `None` if `pid` is not a child of the process.
+    ///
+    /// * `pid` - the PID of the child process.
+    pub fn join(&self, pid: Pid) -> Option<(usize, usize, usize)> {
+        println!("{}: JOIN {}", self.pid, pid);
+        let result = self
+            .processor
+            .scheduler(StopReason::syscall(Syscall::Join(pid)));
+        self.suspend();
+        // A child that is still alive blocks the process until it exits
+        match result {
+            SyscallResult::Joined(timings) => Some(timings),
+            _ => match self.processor.take_wake_result(self.pid) {
+                Some(SyscallResult::Joined(timings)) => Some(timings),
+                _ => None,
+            },
+        }
+    }
+
     fn exit(&self) {
         println!("{}: EXIT", self.pid);
         self.processor.scheduler(StopReason::syscall(Syscall::Exit));
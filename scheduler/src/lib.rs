@@ -6,12 +6,22 @@
 
 use std::num::NonZeroUsize;
 
+mod recorder;
 mod scheduler;
 
-use schedulers::{Empty, RoundRobin, RoundRobinPriority};
+use schedulers::Empty;
 
+pub use schedulers::{
+    nice_to_weight, AdaptiveRoundRobin, Cooperative, DecayRoundRobin, GenericScheduler, LevelStat,
+    NiceRoundRobin, ProcessInfo, RoundRobin, RoundRobinPolicy, RoundRobinPriority,
+    RoundRobinPriorityPolicy, SecondaryKey, SelectionPolicy,
+};
+
+pub use crate::recorder::{replay, Recorder, TraceEvent};
 pub use crate::scheduler::{
-    Pid, Process, ProcessState, Scheduler, SchedulingDecision, StopReason, Syscall, SyscallResult,
+    AloneExpiry, BlockReason, EventSet, IdleReason, Pid, Process, ProcessMeta, ProcessSnapshot,
+    ProcessState, Scheduler, SchedulingDecision, ScriptOp, StopReason, Syscall, SyscallResult,
+    TimeUnit, WakePolicy,
 };
 
 mod schedulers;
@@ -23,11 +33,11 @@ mod schedulers;
 ///
 /// * `timeslice` - the time quanta that a process can run before it is preempted
 /// * `minimum_remaining_timeslice` - when a process makes a system call, the scheduler
-///                                 has to decode whether to schedule it again for the
-///                                 remaining time of its quanta, or to schedule a new
-///                                 process. The scheduler will schedule the process
-///                                 again of the remaining quanta is greater or equal to
-///                                 the `minimum_remaining_timeslice` value.
+///   has to decode whether to schedule it again for the
+///   remaining time of its quanta, or to schedule a new
+///   process. The scheduler will schedule the process
+///   again of the remaining quanta is greater or equal to
+///   the `minimum_remaining_timeslice` value.
 #[allow(unused_variables)]
 pub fn round_robin(timeslice: NonZeroUsize, minimum_remaining_timeslice: usize) -> impl Scheduler {
     RoundRobin::new(timeslice, minimum_remaining_timeslice)
@@ -36,11 +46,11 @@ pub fn round_robin(timeslice: NonZeroUsize, minimum_remaining_timeslice: usize)
 /// Returns a structure that implements the `Scheduler` trait with a priority queue scheduler policy
 /// * `timeslice` - the time quanta that a process can run before it is preempted
 /// * `minimum_remaining_timeslice` - when a process makes a system call, the scheduler
-///                                 has to decode whether to schedule it again for the
-///                                 remaining time of its quanta, or to schedule a new
-///                                 process. The scheduler will schedule the process
-///                                 again of the remaining quanta is greater or equal to
-///                                 the `minimum_remaining_timeslice` value.
+///   has to decode whether to schedule it again for the
+///   remaining time of its quanta, or to schedule a new
+///   process. The scheduler will schedule the process
+///   again of the remaining quanta is greater or equal to
+///   the `minimum_remaining_timeslice` value.
 #[allow(unused_variables)]
 pub fn priority_queue(
     timeslice: NonZeroUsize,
@@ -49,15 +59,64 @@ pub fn priority_queue(
     RoundRobinPriority::new(timeslice, minimum_remaining_timeslice)
 }
 
+/// Returns a structure that implements the `Scheduler` trait with a round robin scheduler policy
+/// that separates interactive processes from batch processes
+/// * `short_slice` - the time quanta of the processes that usually block before it expires
+/// * `long_slice` - the time quanta of the processes that usually use all of it
+/// * `threshold` - the ratio of blocks out of the recent stops of a process
+///   starting from which the process is considered interactive
+pub fn adaptive_round_robin(
+    short_slice: NonZeroUsize,
+    long_slice: NonZeroUsize,
+    threshold: f64,
+) -> impl Scheduler {
+    AdaptiveRoundRobin::new(short_slice, long_slice, threshold)
+}
+
+/// Returns a structure that implements the `Scheduler` trait with a round robin scheduler policy
+/// that shrinks the timeslice of the processes that keep using all of it
+/// * `base` - the time quanta of a process that blocks before it expires
+/// * `floor` - the minimum time quanta that a process can receive
+/// * `decay` - the factor that the time quanta is multiplied with for every
+///   consecutive expiry of a process
+pub fn decay_round_robin(base: NonZeroUsize, floor: NonZeroUsize, decay: f64) -> impl Scheduler {
+    DecayRoundRobin::new(base, floor, decay)
+}
+
+/// Returns a structure that implements the `Scheduler` trait with a round robin scheduler policy
+/// that gives longer timeslices to the processes with lower nice values (priorities)
+/// * `timeslice` - the time quanta of a process with nice 0
+pub fn nice_round_robin(timeslice: NonZeroUsize) -> impl Scheduler {
+    NiceRoundRobin::new(timeslice)
+}
+
+/// Returns a structure that implements the `Scheduler` trait with a round robin scheduler policy
+/// built on the [`GenericScheduler`]
+/// * `timeslice` - the time quanta that a process can run before it is preempted
+/// * `minimum_remaining_timeslice` - the minimum remaining quanta for which a process
+///   is scheduled again after a system call
+pub fn generic_round_robin(
+    timeslice: NonZeroUsize,
+    minimum_remaining_timeslice: usize,
+) -> impl Scheduler {
+    GenericScheduler::<RoundRobinPolicy>::new(timeslice, minimum_remaining_timeslice)
+}
+
+/// Returns a structure that implements the `Scheduler` trait with a cooperative scheduler policy
+/// that never preempts a process, the processes run until they sleep, wait or exit
+pub fn cooperative() -> impl Scheduler {
+    Cooperative::new()
+}
+
 /// Returns a structure that implements the `Scheduler` trait with a simplified [cfs](https://opensource.com/article/19/2/fair-scheduling-linux) scheduler policy
 /// * `cpu_time` - the total time units that the cpu has for an iteration, this is used to compute
-///                    the `timeslice` of each process.
+///   the `timeslice` of each process.
 /// * `minimum_remaining_timeslice` - when a process makes a system call, the scheduler
-///                                 has to decode whether to schedule it again for the
-///                                 remaining time of its quanta, or to schedule a new
-///                                 process. The scheduler will schedule the process
-///                                 again of the remaining quanta is greater or equal to
-///                                 the `minimum_remaining_timeslice` value.
+///   has to decode whether to schedule it again for the
+///   remaining time of its quanta, or to schedule a new
+///   process. The scheduler will schedule the process
+///   again of the remaining quanta is greater or equal to
+///   the `minimum_remaining_timeslice` value.
 #[allow(unused_variables)]
 pub fn cfs(cpu_time: NonZeroUsize, minimum_remaining_timeslice: usize) -> impl Scheduler {
     Empty
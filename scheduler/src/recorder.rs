@@ -0,0 +1,879 @@
+use std::fs;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use crate::{
+    EventSet, Pid, Process, ProcessState, Scheduler, SchedulingDecision, StopReason, Syscall,
+    SyscallResult,
+};
+
+/// The bytes that start a binary trace.
+const TRACE_MAGIC: &[u8; 4] = b"PSTR";
+
+/// The version of the binary trace format written by [`Recorder::write_trace`].
+///
+/// Every version that was ever written has to stay readable, so a change of
+/// the format needs a new version and a new decoder.
+const TRACE_VERSION: u8 = 1;
+
+/// An event recorded by a [`Recorder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    /// The scheduler returned a decision from [`Scheduler::next`].
+    Next {
+        /// The time at which the decision was taken.
+        tick: usize,
+        /// The decision.
+        decision: SchedulingDecision,
+    },
+
+    /// The scheduler was informed through [`Scheduler::stop`] that a process has stopped.
+    Stop {
+        /// The process that was running, if any.
+        pid: Option<Pid>,
+        /// The reason that the process has stopped.
+        reason: StopReason,
+        /// The result returned by the scheduler.
+        result: SyscallResult,
+    },
+}
+
+/// A scheduler that records all the decisions and the stop reasons
+/// of the scheduler that it wraps.
+///
+/// The recording can be saved to a file with [`Recorder::save`] and
+/// later fed to a new scheduler with [`replay`].
+pub struct Recorder<S: Scheduler> {
+    scheduler: S,
+    events: Vec<TraceEvent>,
+    tick: usize,
+    running: Option<(Pid, usize)>, // the running process and its timeslice
+    watched: Option<Pid>,          // the process whose events are also kept apart
+    watched_events: Vec<TraceEvent>, // the events of the watched process
+}
+
+impl<S: Scheduler> Recorder<S> {
+    pub fn new(scheduler: S) -> Self {
+        Self {
+            scheduler,
+            events: Vec::new(),
+            tick: 0,
+            running: None,
+            watched: None,
+            watched_events: Vec::new(),
+        }
+    }
+
+    /// Returns the recorded events.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Starts keeping the events of the process with the given pid apart,
+    /// in [`Recorder::watched_events`], replacing the previously watched
+    /// process and its events.
+    pub fn watch_pid(&mut self, pid: Pid) {
+        self.watched = Some(pid);
+        self.watched_events.clear();
+    }
+
+    /// Returns the events of the watched process recorded since
+    /// [`Recorder::watch_pid`] was called: the decisions that run it, its
+    /// stops (when it blocks or exits), and the events after which it
+    /// stopped waiting (when it is woken up).
+    pub fn watched_events(&self) -> &[TraceEvent] {
+        &self.watched_events
+    }
+
+    /// Returns the time elapsed since the recording started.
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Returns the wrapped scheduler.
+    pub fn into_inner(self) -> S {
+        self.scheduler
+    }
+
+    /// Renders the recorded decisions as a textual Gantt chart, like `P1|P1|P2|--|P1`.
+    ///
+    /// Every decision is a segment: `P<pid>` for a process that runs, `--` for
+    /// a sleeping processor, `..` for a processor kept idle by the scheduler,
+    /// `XX` for a deadlock, `!!` for a panic, `S<pid>` for a starving process
+    /// and `?<pid>` for a stranded process. If `compress` is `true`,
+    /// consecutive identical segments are merged into one.
+    pub fn gantt(&self, compress: bool) -> String {
+        let mut segments: Vec<String> = Vec::new();
+        for event in &self.events {
+            let TraceEvent::Next { decision, .. } = event else {
+                continue;
+            };
+            let segment = match decision {
+                SchedulingDecision::Run { pid, .. } => format!("P{pid}"),
+                SchedulingDecision::Sleep(_) => String::from("--"),
+                SchedulingDecision::Idle(_) => String::from(".."),
+                SchedulingDecision::Deadlock => String::from("XX"),
+                SchedulingDecision::Panic => String::from("!!"),
+                SchedulingDecision::Starvation(pid) => format!("S{pid}"),
+                SchedulingDecision::Stranded(pid) => format!("?{pid}"),
+                SchedulingDecision::Done => continue,
+            };
+            if compress && segments.last() == Some(&segment) {
+                continue;
+            }
+            segments.push(segment);
+        }
+        segments.join("|")
+    }
+
+    /// Writes the recorded events to a file, one event per line.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut contents = String::new();
+        for event in &self.events {
+            contents.push_str(&encode_event(event));
+            contents.push('\n');
+        }
+        fs::write(path, contents)
+    }
+
+    /// Writes the recorded events in a compact binary format, that can be
+    /// read back with [`Recorder::read_trace`].
+    ///
+    /// The trace starts with the format version, and the numbers are
+    /// written with a variable length, so small numbers take one byte.
+    pub fn write_trace(&self, w: impl Write) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+        w.write_all(TRACE_MAGIC)?;
+        w.write_all(&[TRACE_VERSION])?;
+        for event in &self.events {
+            write_binary_event(&mut w, event)?;
+        }
+        w.flush()
+    }
+
+    /// Reads the events written by [`Recorder::write_trace`], with any
+    /// version of the binary format.
+    pub fn read_trace(r: impl Read) -> io::Result<Vec<TraceEvent>> {
+        let mut r = BufReader::new(r);
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != TRACE_MAGIC {
+            return Err(invalid_binary("not a binary trace"));
+        }
+        let version = read_u8(&mut r)?;
+        let mut events = Vec::new();
+        match version {
+            1 => {
+                while let Some(event) = read_binary_event_v1(&mut r)? {
+                    events.push(event);
+                }
+            }
+            _ => return Err(invalid_binary(&format!("unsupported version {version}"))),
+        }
+        Ok(events)
+    }
+
+    /// Reads the events saved by [`Recorder::save`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<TraceEvent>> {
+        fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(decode_event)
+            .collect()
+    }
+
+    fn watched_is_waiting(&mut self) -> bool {
+        let Some(watched) = self.watched else {
+            return false;
+        };
+        self.scheduler
+            .waiting_list()
+            .iter()
+            .any(|proc| proc.pid() == watched)
+    }
+
+    fn push_event(&mut self, event: TraceEvent, was_waiting: bool) {
+        if let Some(watched) = self.watched {
+            let involved = match &event {
+                TraceEvent::Next { decision, .. } => matches!(
+                    decision,
+                    SchedulingDecision::Run { pid, .. }
+                    | SchedulingDecision::Starvation(pid)
+                    | SchedulingDecision::Stranded(pid) if *pid == watched
+                ),
+                TraceEvent::Stop { pid, .. } => *pid == Some(watched),
+            };
+            // A process that stopped waiting was woken up by this event
+            if involved || (was_waiting && !self.watched_is_waiting()) {
+                self.watched_events.push(event.clone());
+            }
+        }
+        self.events.push(event);
+    }
+}
+
+impl<S: Scheduler> Scheduler for Recorder<S> {
+    fn next(&mut self) -> SchedulingDecision {
+        let was_waiting = self.watched_is_waiting();
+        let decision = self.scheduler.next();
+        self.push_event(
+            TraceEvent::Next {
+                tick: self.tick,
+                decision,
+            },
+            was_waiting,
+        );
+        match decision {
+            SchedulingDecision::Run { pid, timeslice } => {
+                self.running = Some((pid, timeslice.get()));
+            }
+            SchedulingDecision::Sleep(amount) | SchedulingDecision::Idle(amount) => {
+                self.tick += amount.get();
+            }
+            _ => {}
+        }
+        decision
+    }
+
+    fn stop(&mut self, reason: StopReason) -> SyscallResult {
+        let was_waiting = self.watched_is_waiting();
+        let result = self.scheduler.stop(reason.clone());
+        let running = self.running.take();
+        // Advance the clock with the time used by the running process
+        if let Some((_, timeslice)) = running {
+            self.tick += match &reason {
+                StopReason::Expired => timeslice,
+                StopReason::Syscall { remaining, .. } => timeslice.saturating_sub(*remaining),
+            };
+        }
+        // A process that keeps running after a system call goes on with the rest of its timeslice
+        if let (Some((pid, _)), StopReason::Syscall { remaining, .. }) = (running, &reason) {
+            let still_running = self
+                .scheduler
+                .list()
+                .iter()
+                .any(|proc| proc.pid() == pid && proc.state() == ProcessState::Running);
+            if still_running {
+                self.running = Some((pid, *remaining));
+            }
+        }
+        self.push_event(
+            TraceEvent::Stop {
+                pid: running.map(|(pid, _)| pid),
+                reason,
+                result,
+            },
+            was_waiting,
+        );
+        result
+    }
+
+    fn list(&mut self) -> Vec<&dyn Process> {
+        self.scheduler.list()
+    }
+
+    fn ready_list(&mut self) -> Vec<&dyn Process> {
+        self.scheduler.ready_list()
+    }
+
+    fn waiting_list(&mut self) -> Vec<&dyn Process> {
+        self.scheduler.waiting_list()
+    }
+
+    fn take_wake_result(&mut self, pid: Pid) -> Option<SyscallResult> {
+        self.scheduler.take_wake_result(pid)
+    }
+
+    fn remaining_timeslice(&self) -> Option<NonZeroUsize> {
+        self.scheduler.remaining_timeslice()
+    }
+
+    fn describe(&self) -> String {
+        self.scheduler.describe()
+    }
+
+    fn is_idle(&mut self) -> bool {
+        self.scheduler.is_idle()
+    }
+
+    fn validate_invariants(&mut self) -> Result<(), String> {
+        self.scheduler.validate_invariants()
+    }
+}
+
+/// Feeds a recording saved by [`Recorder::save`] to `scheduler`.
+///
+/// Every stop reason is replayed in order and every decision of the
+/// scheduler is compared with the recorded one.
+///
+/// ## Panics
+///
+/// If a decision or a system call result of the scheduler does not match the recording.
+pub fn replay<S: Scheduler>(path: impl AsRef<Path>, scheduler: &mut S) -> io::Result<()> {
+    for (index, event) in Recorder::<S>::load(path)?.into_iter().enumerate() {
+        match event {
+            TraceEvent::Next { decision, .. } => {
+                assert_eq!(
+                    scheduler.next(),
+                    decision,
+                    "event {index}: the decision does not match the recording"
+                );
+            }
+            TraceEvent::Stop { reason, result, .. } => {
+                assert_eq!(
+                    scheduler.stop(reason),
+                    result,
+                    "event {index}: the result does not match the recording"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encode_event(event: &TraceEvent) -> String {
+    match event {
+        TraceEvent::Next { tick, decision } => {
+            let decision = match decision {
+                SchedulingDecision::Run { pid, timeslice } => format!("run {pid} {timeslice}"),
+                SchedulingDecision::Sleep(amount) => format!("sleep {amount}"),
+                SchedulingDecision::Idle(amount) => format!("idle {amount}"),
+                SchedulingDecision::Deadlock => String::from("deadlock"),
+                SchedulingDecision::Panic => String::from("panic"),
+                SchedulingDecision::Starvation(pid) => format!("starvation {pid}"),
+                SchedulingDecision::Stranded(pid) => format!("stranded {pid}"),
+                SchedulingDecision::Done => String::from("done"),
+            };
+            format!("next {tick} {decision}")
+        }
+        TraceEvent::Stop {
+            pid,
+            reason,
+            result,
+        } => {
+            let pid = match pid {
+                Some(pid) => pid.to_string(),
+                None => String::from("-"),
+            };
+            let reason = match reason {
+                StopReason::Expired => String::from("expired"),
+                StopReason::Syscall { syscall, remaining } => {
+                    let syscall = match syscall {
+                        Syscall::Fork(priority) => format!("fork {priority}"),
+                        Syscall::ForkDeferred(priority) => format!("fork_deferred {priority}"),
+                        Syscall::Sleep(amount) => format!("sleep {amount}"),
+                        Syscall::Wait(event) => format!("wait {event}"),
+                        Syscall::WaitAny(events) if events.is_empty() => String::from("wait_any -"),
+                        Syscall::WaitAny(events) => {
+                            let events: Vec<String> =
+                                events.iter().map(|event| event.to_string()).collect();
+                            format!("wait_any {}", events.join(","))
+                        }
+                        Syscall::Signal(event) => format!("signal {event}"),
+                        Syscall::SignalOne(event) => format!("signal_one {event}"),
+                        Syscall::Exit => String::from("exit"),
+                        Syscall::PreemptDisable => String::from("preempt_disable"),
+                        Syscall::PreemptEnable => String::from("preempt_enable"),
+                        Syscall::BlockIo {
+                            device,
+                            base_latency,
+                        } => format!("block_io {device} {base_latency}"),
+                        Syscall::Join(pid) => format!("join {pid}"),
+                        Syscall::Donate { to, amount } => format!("donate {to} {amount}"),
+                        Syscall::AcquireLock => String::from("acquire_lock"),
+                        Syscall::ReleaseLock => String::from("release_lock"),
+                    };
+                    format!("syscall {remaining} {syscall}")
+                }
+            };
+            let result = match result {
+                SyscallResult::Pid(pid) => format!("pid {pid}"),
+                SyscallResult::Joined((total, syscalls, execution)) => {
+                    format!("joined {total} {syscalls} {execution}")
+                }
+                SyscallResult::Event(event) => format!("event {event}"),
+                SyscallResult::Success => String::from("success"),
+                SyscallResult::NoRunningProcess => String::from("no_running_process"),
+            };
+            format!("stop {pid} {reason} => {result}")
+        }
+    }
+}
+
+fn invalid(line: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("invalid trace event: {line}"),
+    )
+}
+
+fn decode_event(line: &str) -> io::Result<TraceEvent> {
+    let mut tokens = line.split_whitespace();
+    let mut token = || tokens.next().ok_or_else(|| invalid(line));
+    let event = match token()? {
+        "next" => {
+            let tick = parse(line, token()?)?;
+            let decision = match token()? {
+                "run" => SchedulingDecision::Run {
+                    pid: Pid::new(parse_non_zero(line, token()?)?.get()),
+                    timeslice: parse_non_zero(line, token()?)?,
+                },
+                "sleep" => SchedulingDecision::Sleep(parse_non_zero(line, token()?)?),
+                "idle" => SchedulingDecision::Idle(parse_non_zero(line, token()?)?),
+                "deadlock" => SchedulingDecision::Deadlock,
+                "panic" => SchedulingDecision::Panic,
+                "starvation" => {
+                    SchedulingDecision::Starvation(Pid::new(parse_non_zero(line, token()?)?.get()))
+                }
+                "stranded" => {
+                    SchedulingDecision::Stranded(Pid::new(parse_non_zero(line, token()?)?.get()))
+                }
+                "done" => SchedulingDecision::Done,
+                _ => return Err(invalid(line)),
+            };
+            TraceEvent::Next { tick, decision }
+        }
+        "stop" => {
+            let pid = match token()? {
+                "-" => None,
+                pid => Some(Pid::new(parse_non_zero(line, pid)?.get())),
+            };
+            let reason = match token()? {
+                "expired" => StopReason::Expired,
+                "syscall" => {
+                    let remaining = parse(line, token()?)?;
+                    let syscall = match token()? {
+                        "fork" => Syscall::Fork(parse(line, token()?)?),
+                        "fork_deferred" => Syscall::ForkDeferred(parse(line, token()?)?),
+                        "sleep" => Syscall::Sleep(parse(line, token()?)?),
+                        "wait" => Syscall::Wait(parse(line, token()?)?),
+                        "wait_any" => match token()? {
+                            "-" => Syscall::WaitAny(EventSet::default()),
+                            events => Syscall::WaitAny(
+                                events
+                                    .split(',')
+                                    .map(|event| parse(line, event))
+                                    .collect::<io::Result<_>>()?,
+                            ),
+                        },
+                        "signal" => Syscall::Signal(parse(line, token()?)?),
+                        "signal_one" => Syscall::SignalOne(parse(line, token()?)?),
+                        "exit" => Syscall::Exit,
+                        "preempt_disable" => Syscall::PreemptDisable,
+                        "preempt_enable" => Syscall::PreemptEnable,
+                        "block_io" => Syscall::BlockIo {
+                            device: parse(line, token()?)?,
+                            base_latency: parse(line, token()?)?,
+                        },
+                        "join" => Syscall::Join(Pid::new(parse_non_zero(line, token()?)?.get())),
+                        "donate" => Syscall::Donate {
+                            to: Pid::new(parse_non_zero(line, token()?)?.get()),
+                            amount: parse(line, token()?)?,
+                        },
+                        "acquire_lock" => Syscall::AcquireLock,
+                        "release_lock" => Syscall::ReleaseLock,
+                        _ => return Err(invalid(line)),
+                    };
+                    StopReason::Syscall { syscall, remaining }
+                }
+                _ => return Err(invalid(line)),
+            };
+            if token()? != "=>" {
+                return Err(invalid(line));
+            }
+            let result = match token()? {
+                "pid" => SyscallResult::Pid(Pid::new(parse_non_zero(line, token()?)?.get())),
+                "joined" => SyscallResult::Joined((
+                    parse(line, token()?)?,
+                    parse(line, token()?)?,
+                    parse(line, token()?)?,
+                )),
+                "event" => SyscallResult::Event(parse(line, token()?)?),
+                "success" => SyscallResult::Success,
+                "no_running_process" => SyscallResult::NoRunningProcess,
+                _ => return Err(invalid(line)),
+            };
+            TraceEvent::Stop {
+                pid,
+                reason,
+                result,
+            }
+        }
+        _ => return Err(invalid(line)),
+    };
+    Ok(event)
+}
+
+fn parse<T: std::str::FromStr>(line: &str, token: &str) -> io::Result<T> {
+    token.parse().map_err(|_| invalid(line))
+}
+
+fn parse_non_zero(line: &str, token: &str) -> io::Result<NonZeroUsize> {
+    parse(line, token)
+}
+
+fn invalid_binary(reason: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("invalid binary trace: {reason}"),
+    )
+}
+
+fn write_number(w: &mut impl Write, mut value: usize) -> io::Result<()> {
+    // 7 bits in every byte, the highest bit is set if more bytes follow
+    while value >= 0x80 {
+        w.write_all(&[value as u8 | 0x80])?;
+        value >>= 7;
+    }
+    w.write_all(&[value as u8])
+}
+
+fn read_u8(r: &mut impl Read) -> io::Result<u8> {
+    let mut byte = [0];
+    r.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_number(r: &mut impl Read) -> io::Result<usize> {
+    let mut value = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let byte = read_u8(r)?;
+        value |= ((byte & 0x7f) as usize)
+            .checked_shl(shift)
+            .ok_or_else(|| invalid_binary("number too large"))?;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_binary("number too large"))
+}
+
+fn read_non_zero(r: &mut impl Read) -> io::Result<NonZeroUsize> {
+    NonZeroUsize::new(read_number(r)?).ok_or_else(|| invalid_binary("unexpected zero"))
+}
+
+fn read_pid(r: &mut impl Read) -> io::Result<Pid> {
+    Ok(Pid::new(read_non_zero(r)?.get()))
+}
+
+fn write_binary_event(w: &mut impl Write, event: &TraceEvent) -> io::Result<()> {
+    match event {
+        TraceEvent::Next { tick, decision } => {
+            w.write_all(&[0])?;
+            write_number(w, *tick)?;
+            match decision {
+                SchedulingDecision::Run { pid, timeslice } => {
+                    w.write_all(&[0])?;
+                    write_number(w, pid.get())?;
+                    write_number(w, timeslice.get())
+                }
+                SchedulingDecision::Sleep(amount) => {
+                    w.write_all(&[1])?;
+                    write_number(w, amount.get())
+                }
+                SchedulingDecision::Deadlock => w.write_all(&[2]),
+                SchedulingDecision::Panic => w.write_all(&[3]),
+                SchedulingDecision::Starvation(pid) => {
+                    w.write_all(&[4])?;
+                    write_number(w, pid.get())
+                }
+                SchedulingDecision::Stranded(pid) => {
+                    w.write_all(&[5])?;
+                    write_number(w, pid.get())
+                }
+                SchedulingDecision::Done => w.write_all(&[6]),
+                SchedulingDecision::Idle(amount) => {
+                    w.write_all(&[7])?;
+                    write_number(w, amount.get())
+                }
+            }
+        }
+        TraceEvent::Stop {
+            pid,
+            reason,
+            result,
+        } => {
+            w.write_all(&[1])?;
+            // Pids are never 0, so 0 stands for no process
+            write_number(w, pid.map_or(0, Pid::get))?;
+            match reason {
+                StopReason::Expired => w.write_all(&[0])?,
+                StopReason::Syscall { syscall, remaining } => {
+                    w.write_all(&[1])?;
+                    write_number(w, *remaining)?;
+                    match syscall {
+                        Syscall::Fork(priority) => w.write_all(&[0, *priority as u8])?,
+                        Syscall::ForkDeferred(priority) => w.write_all(&[1, *priority as u8])?,
+                        Syscall::Sleep(amount) => {
+                            w.write_all(&[2])?;
+                            write_number(w, *amount)?;
+                        }
+                        Syscall::Wait(event) => {
+                            w.write_all(&[3])?;
+                            write_number(w, *event)?;
+                        }
+                        Syscall::WaitAny(events) => {
+                            w.write_all(&[4])?;
+                            write_number(w, events.len())?;
+                            for event in events.iter() {
+                                write_number(w, event)?;
+                            }
+                        }
+                        Syscall::Signal(event) => {
+                            w.write_all(&[5])?;
+                            write_number(w, *event)?;
+                        }
+                        Syscall::SignalOne(event) => {
+                            w.write_all(&[6])?;
+                            write_number(w, *event)?;
+                        }
+                        Syscall::Exit => w.write_all(&[7])?,
+                        Syscall::PreemptDisable => w.write_all(&[8])?,
+                        Syscall::PreemptEnable => w.write_all(&[9])?,
+                        Syscall::BlockIo {
+                            device,
+                            base_latency,
+                        } => {
+                            w.write_all(&[10])?;
+                            write_number(w, *device)?;
+                            write_number(w, *base_latency)?;
+                        }
+                        Syscall::Join(pid) => {
+                            w.write_all(&[11])?;
+                            write_number(w, pid.get())?;
+                        }
+                        Syscall::Donate { to, amount } => {
+                            w.write_all(&[12])?;
+                            write_number(w, to.get())?;
+                            w.write_all(&[*amount as u8])?;
+                        }
+                        Syscall::AcquireLock => w.write_all(&[13])?,
+                        Syscall::ReleaseLock => w.write_all(&[14])?,
+                    }
+                }
+            }
+            match result {
+                SyscallResult::Pid(pid) => {
+                    w.write_all(&[0])?;
+                    write_number(w, pid.get())
+                }
+                SyscallResult::Joined((total, syscalls, execution)) => {
+                    w.write_all(&[1])?;
+                    write_number(w, *total)?;
+                    write_number(w, *syscalls)?;
+                    write_number(w, *execution)
+                }
+                SyscallResult::Event(event) => {
+                    w.write_all(&[2])?;
+                    write_number(w, *event)
+                }
+                SyscallResult::Success => w.write_all(&[3]),
+                SyscallResult::NoRunningProcess => w.write_all(&[4]),
+            }
+        }
+    }
+}
+
+fn read_binary_event_v1(r: &mut impl Read) -> io::Result<Option<TraceEvent>> {
+    // The trace ends after the last complete event
+    let mut tag = [0];
+    if r.read(&mut tag)? == 0 {
+        return Ok(None);
+    }
+    let event = match tag[0] {
+        0 => {
+            let tick = read_number(r)?;
+            let decision = match read_u8(r)? {
+                0 => SchedulingDecision::Run {
+                    pid: read_pid(r)?,
+                    timeslice: read_non_zero(r)?,
+                },
+                1 => SchedulingDecision::Sleep(read_non_zero(r)?),
+                2 => SchedulingDecision::Deadlock,
+                3 => SchedulingDecision::Panic,
+                4 => SchedulingDecision::Starvation(read_pid(r)?),
+                5 => SchedulingDecision::Stranded(read_pid(r)?),
+                6 => SchedulingDecision::Done,
+                7 => SchedulingDecision::Idle(read_non_zero(r)?),
+                tag => return Err(invalid_binary(&format!("unknown decision {tag}"))),
+            };
+            TraceEvent::Next { tick, decision }
+        }
+        1 => {
+            let pid = NonZeroUsize::new(read_number(r)?).map(|pid| Pid::new(pid.get()));
+            let reason = match read_u8(r)? {
+                0 => StopReason::Expired,
+                1 => {
+                    let remaining = read_number(r)?;
+                    let syscall = match read_u8(r)? {
+                        0 => Syscall::Fork(read_u8(r)? as i8),
+                        1 => Syscall::ForkDeferred(read_u8(r)? as i8),
+                        2 => Syscall::Sleep(read_number(r)?),
+                        3 => Syscall::Wait(read_number(r)?),
+                        4 => {
+                            let count = read_number(r)?;
+                            let mut events = EventSet::default();
+                            for _ in 0..count {
+                                events.insert(read_number(r)?);
+                            }
+                            Syscall::WaitAny(events)
+                        }
+                        5 => Syscall::Signal(read_number(r)?),
+                        6 => Syscall::SignalOne(read_number(r)?),
+                        7 => Syscall::Exit,
+                        8 => Syscall::PreemptDisable,
+                        9 => Syscall::PreemptEnable,
+                        10 => Syscall::BlockIo {
+                            device: read_number(r)?,
+                            base_latency: read_number(r)?,
+                        },
+                        11 => Syscall::Join(read_pid(r)?),
+                        12 => Syscall::Donate {
+                            to: read_pid(r)?,
+                            amount: read_u8(r)? as i8,
+                        },
+                        13 => Syscall::AcquireLock,
+                        14 => Syscall::ReleaseLock,
+                        tag => return Err(invalid_binary(&format!("unknown system call {tag}"))),
+                    };
+                    StopReason::Syscall { syscall, remaining }
+                }
+                tag => return Err(invalid_binary(&format!("unknown stop reason {tag}"))),
+            };
+            let result = match read_u8(r)? {
+                0 => SyscallResult::Pid(read_pid(r)?),
+                1 => SyscallResult::Joined((read_number(r)?, read_number(r)?, read_number(r)?)),
+                2 => SyscallResult::Event(read_number(r)?),
+                3 => SyscallResult::Success,
+                4 => SyscallResult::NoRunningProcess,
+                tag => return Err(invalid_binary(&format!("unknown result {tag}"))),
+            };
+            TraceEvent::Stop {
+                pid,
+                reason,
+                result,
+            }
+        }
+        tag => return Err(invalid_binary(&format!("unknown event {tag}"))),
+    };
+    Ok(Some(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RoundRobin;
+
+    fn syscall<S: Scheduler>(scheduler: &mut S, syscall: Syscall, remaining: usize) {
+        scheduler.stop(StopReason::Syscall { syscall, remaining });
+    }
+
+    #[test]
+    fn a_binary_trace_reads_back_the_recorded_events() {
+        let mut recorder = Recorder::new(RoundRobin::new(NonZeroUsize::new(200).unwrap(), 1));
+        syscall(&mut recorder, Syscall::Fork(0), 0);
+        recorder.next();
+        syscall(&mut recorder, Syscall::Fork(-3), 199);
+        syscall(
+            &mut recorder,
+            Syscall::WaitAny(EventSet::new(&[3, 63, 1000])),
+            150,
+        );
+        recorder.next();
+        syscall(&mut recorder, Syscall::Signal(1000), 100);
+        syscall(&mut recorder, Syscall::Sleep(1000), 90);
+        recorder.next();
+        recorder.stop(StopReason::Expired);
+        recorder.next();
+
+        let mut trace = Vec::new();
+        recorder.write_trace(&mut trace).unwrap();
+        assert_eq!(&trace[..5], b"PSTR\x01");
+        let events = Recorder::<RoundRobin>::read_trace(trace.as_slice()).unwrap();
+        assert_eq!(events.len(), 10);
+        assert_eq!(events, recorder.events());
+    }
+
+    #[test]
+    fn only_the_events_of_the_watched_process_are_kept_apart() {
+        let mut recorder = Recorder::new(RoundRobin::new(NonZeroUsize::new(5).unwrap(), 1));
+        syscall(&mut recorder, Syscall::Fork(0), 0);
+        recorder.next();
+        syscall(&mut recorder, Syscall::Fork(0), 4);
+        recorder.watch_pid(Pid::new(2));
+        recorder.stop(StopReason::Expired);
+        recorder.next();
+        syscall(&mut recorder, Syscall::Wait(1), 3);
+        recorder.next();
+        syscall(&mut recorder, Syscall::Fork(0), 4);
+        // Process 1 wakes up process 2
+        syscall(&mut recorder, Syscall::Signal(1), 3);
+        recorder.stop(StopReason::Expired);
+        recorder.next();
+        recorder.stop(StopReason::Expired);
+        recorder.next();
+        recorder.stop(StopReason::Expired);
+
+        let involved: Vec<_> = recorder
+            .watched_events()
+            .iter()
+            .map(|event| match event {
+                TraceEvent::Next {
+                    decision: SchedulingDecision::Run { pid, .. },
+                    ..
+                } => (*pid, None),
+                TraceEvent::Stop { pid, reason, .. } => (pid.unwrap(), Some(reason.clone())),
+                event => panic!("unexpected event {event:?}"),
+            })
+            .collect();
+        let stop = |syscall, remaining| Some(StopReason::Syscall { syscall, remaining });
+        assert_eq!(
+            involved,
+            [
+                (Pid::new(2), None),
+                (Pid::new(2), stop(Syscall::Wait(1), 3)),
+                (Pid::new(1), stop(Syscall::Signal(1), 3)),
+                (Pid::new(2), None),
+                (Pid::new(2), Some(StopReason::Expired)),
+            ]
+        );
+        assert_eq!(recorder.events().len(), 14);
+        assert_eq!(recorder.tick(), 22);
+    }
+
+    #[test]
+    fn a_binary_trace_of_the_first_version_stays_readable() {
+        // A run decision at tick 3 and the fork of process 2 with 300 time units left
+        let trace = b"PSTR\x01\x00\x03\x00\x01\x05\x01\x00\x01\xac\x02\x00\x00\x00\x02";
+        let events = Recorder::<RoundRobin>::read_trace(&trace[..]).unwrap();
+        assert_eq!(
+            events,
+            [
+                TraceEvent::Next {
+                    tick: 3,
+                    decision: SchedulingDecision::Run {
+                        pid: Pid::new(1),
+                        timeslice: NonZeroUsize::new(5).unwrap(),
+                    },
+                },
+                TraceEvent::Stop {
+                    pid: None,
+                    reason: StopReason::Syscall {
+                        syscall: Syscall::Fork(0),
+                        remaining: 300,
+                    },
+                    result: SyscallResult::Pid(Pid::new(2)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn an_unknown_binary_trace_is_rejected() {
+        for trace in [&b"PSTR\x02"[..], &b"TRACE\x01"[..]] {
+            let error = Recorder::<RoundRobin>::read_trace(trace).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+    }
+}
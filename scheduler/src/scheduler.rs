@@ -1,3 +1,4 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{self, Display};
 use std::num::NonZeroUsize;
 use std::ops::Add;
@@ -13,6 +14,10 @@ impl Pid {
     pub fn new(pid: usize) -> Pid {
         Pid(NonZeroUsize::new(pid).unwrap())
     }
+
+    pub(crate) fn get(self) -> usize {
+        self.0.get()
+    }
 }
 
 impl PartialEq<usize> for Pid {
@@ -49,7 +54,18 @@ pub enum SchedulingDecision {
     /// Run the process with PID `pid` for a maximum of `timeslice` time units.
     Run { pid: Pid, timeslice: NonZeroUsize },
     /// Sleep the amount of specified time units.
+    ///
+    /// Returned when all the processes are blocked on their own, e.g. with a
+    /// [`Syscall::Sleep`] or a [`Syscall::BlockIo`] system call, until the
+    /// first one of them wakes up.
     Sleep(NonZeroUsize),
+    /// Keep the processor idle for the amount of specified time units.
+    ///
+    /// Returned instead of [`SchedulingDecision::Sleep`] when the scheduler
+    /// itself holds back the processes that could run, e.g. the suspended
+    /// processes or the ones that have used their CPU quota. The OS handles
+    /// it the same as a sleep.
+    Idle(NonZeroUsize),
     /// The OS cannot continue anymore, as all the processes are waiting for events.
     ///
     /// In this case there is no other process that can fie any events, which means
@@ -59,6 +75,15 @@ pub enum SchedulingDecision {
     /// The process with PID 1 has stopped.
     Panic,
 
+    /// The process with PID `pid` has been ready to run for longer than
+    /// the starvation limit of the scheduler.
+    Starvation(Pid),
+
+    /// The scheduler has no more work, but the process with PID `pid` has
+    /// not exited. Returned instead of [`SchedulingDecision::Done`] by the
+    /// schedulers that check for stranded processes.
+    Stranded(Pid),
+
     /// There are no more processes to schedule.
     Done,
 }
@@ -72,12 +97,21 @@ impl Display for SchedulingDecision {
             SchedulingDecision::Sleep(amount) => {
                 write!(f, "Sleep for {} slices", amount)
             }
+            SchedulingDecision::Idle(amount) => {
+                write!(f, "Idle for {} slices", amount)
+            }
             SchedulingDecision::Deadlock => {
                 write!(f, "Deadlock, unable to schedule anymore processes")
             }
             SchedulingDecision::Panic => {
                 write!(f, "Panic, process 1 has stopped")
             }
+            SchedulingDecision::Starvation(pid) => {
+                write!(f, "Starvation, process {} did not run for too long", pid)
+            }
+            SchedulingDecision::Stranded(pid) => {
+                write!(f, "Stranded, process {} can not finish", pid)
+            }
             SchedulingDecision::Done => {
                 write!(f, "Done, no more processes")
             }
@@ -85,8 +119,166 @@ impl Display for SchedulingDecision {
     }
 }
 
-/// A system call that processes make towards the scheduler.
+/// The process that a [`Syscall::SignalOne`] system call wakes up.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum WakePolicy {
+    /// The process that has been waiting for the event for the longest time.
+    #[default]
+    LongestWaiting,
+
+    /// The process with the highest priority, or the one that has been
+    /// waiting for the longest time out of the processes with the same priority.
+    HighestPriority,
+}
+
+/// What happens when the timeslice of the running process expires and no
+/// other process is ready.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum AloneExpiry {
+    /// The process is moved to the ready queue and dispatched again, the same
+    /// as when other processes are ready. Its CPU burst ends, an involuntary
+    /// switch is counted and the dispatch latency is charged again.
+    #[default]
+    Requeue,
+
+    /// The process keeps running with a new timeslice, without being
+    /// dispatched again, so no dispatch latency is charged. Its CPU burst
+    /// still ends and an involuntary switch is counted, as if it was
+    /// dispatched again.
+    Renew,
+
+    /// The process keeps running with a new timeslice, as if its timeslice
+    /// did not expire. Its CPU burst continues, so the burst histogram sees a
+    /// single long burst, and no switch is counted.
+    Continue,
+}
+
+/// The unit in which a scheduler reports its times.
+///
+/// The schedulers always count time in ticks, a unit converts the ticks
+/// only when they are reported.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum TimeUnit {
+    /// The ticks of the scheduler, without any conversion.
+    #[default]
+    Ticks,
+
+    /// Nanoseconds, with the given number of nanoseconds in a tick.
+    Nanoseconds(NonZeroUsize),
+
+    /// Microseconds, with the given number of microseconds in a tick.
+    Microseconds(NonZeroUsize),
+
+    /// Milliseconds, with the given number of milliseconds in a tick.
+    Milliseconds(NonZeroUsize),
+}
+
+impl TimeUnit {
+    /// Converts a number of ticks to this unit.
+    pub fn scale(&self, ticks: usize) -> usize {
+        match self {
+            TimeUnit::Ticks => ticks,
+            TimeUnit::Nanoseconds(factor)
+            | TimeUnit::Microseconds(factor)
+            | TimeUnit::Milliseconds(factor) => ticks * factor.get(),
+        }
+    }
+}
+
+impl Display for TimeUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeUnit::Ticks => write!(f, "ticks"),
+            TimeUnit::Nanoseconds(_) => write!(f, "ns"),
+            TimeUnit::Microseconds(_) => write!(f, "us"),
+            TimeUnit::Milliseconds(_) => write!(f, "ms"),
+        }
+    }
+}
+
+/// The reason that the scheduler did not run any process.
 #[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IdleReason {
+    /// There are no more processes, the scheduler returned [`SchedulingDecision::Done`].
+    NoProcesses,
+
+    /// All the processes are sleeping, the scheduler returned [`SchedulingDecision::Sleep`].
+    AllSleeping,
+
+    /// The next process to become ready has used its CPU quota, the scheduler
+    /// returned [`SchedulingDecision::Idle`] until the next quota period.
+    Throttled,
+
+    /// All the processes are waiting for events, the scheduler returned
+    /// [`SchedulingDecision::Deadlock`].
+    Deadlock,
+
+    /// The process with PID 1 has stopped, the scheduler returned
+    /// [`SchedulingDecision::Panic`].
+    InitExited,
+
+    /// The simulation reached its maximum length, the scheduler returned
+    /// [`SchedulingDecision::Done`] while there were still processes.
+    TickLimit,
+
+    /// The only processes that could run are suspended, the scheduler returned
+    /// [`SchedulingDecision::Idle`] until one of them is resumed.
+    Suspended,
+}
+
+/// A set of event numbers, waited for with [`Syscall::WaitAny`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EventSet(BTreeSet<usize>);
+
+impl EventSet {
+    /// Returns a set with the given events.
+    pub fn new(events: &[usize]) -> EventSet {
+        events.iter().copied().collect()
+    }
+
+    /// Adds `event` to the set.
+    pub fn insert(&mut self, event: usize) {
+        self.0.insert(event);
+    }
+
+    /// Returns `true` if `event` is in the set.
+    pub fn contains(&self, event: usize) -> bool {
+        self.0.contains(&event)
+    }
+
+    /// Returns `true` if the set has no events.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of events in the set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the lowest event of the set.
+    pub fn first(&self) -> Option<usize> {
+        self.0.first().copied()
+    }
+
+    /// Returns the events of the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl FromIterator<usize> for EventSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(events: I) -> Self {
+        let mut set = EventSet::default();
+        for event in events {
+            set.insert(event);
+        }
+        set
+    }
+}
+
+/// A system call that processes make towards the scheduler.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Syscall {
     /// Create a new process and return its PID.
     Fork(
@@ -94,6 +286,21 @@ pub enum Syscall {
         i8,
     ),
 
+    /// Create a new process the next time that the process runs.
+    ///
+    /// The new process is not created right away. It is created and placed
+    /// at the end of the ready queue when the process gets its next quanta,
+    /// right before the process resumes, either because the scheduler
+    /// dispatches it again or because it keeps running for a new quanta. The
+    /// process keeps running, so a process always runs again before the
+    /// children that it deferred. The children of a process that exits or is
+    /// killed before it runs again are never created. Some scheduling
+    /// algorithms can ignore this system call.
+    ForkDeferred(
+        /// The priority of the new process. Some scheduling algorithms can ignore this value.
+        i8,
+    ),
+
     /// Ask the scheduler to suspend for an amount of time
     Sleep(
         /// The amount of time that the process should sleep. The process
@@ -110,6 +317,15 @@ pub enum Syscall {
         usize,
     ),
 
+    /// Wait for any of several events.
+    WaitAny(
+        /// The event numbers. The process will be placed in the [`ProcessState::Waiting`]
+        /// state until another process issues a [`Syscall::Signal`] system call with
+        /// one of these event numbers. The event that woke it up is then returned
+        /// by [`Scheduler::take_wake_result`] as a [`SyscallResult::Event`].
+        EventSet,
+    ),
+
     /// Signal all processes that wait for an event.
     Signal(
         /// The event number. All processes that are waiting for this event
@@ -117,11 +333,94 @@ pub enum Syscall {
         usize,
     ),
 
+    /// Signal one of the processes that wait for an event.
+    SignalOne(
+        /// The event number. One of the processes that are waiting for this
+        /// event, chosen by the [`WakePolicy`] of the scheduler, will be woken
+        /// up and placed in the [`ProcessState::Ready`] state. The other ones
+        /// keep waiting. Some scheduling algorithms always wake up the process
+        /// that has been waiting for the longest time.
+        usize,
+    ),
+
     /// Ask the scheduler to finish the process.
     ///
     /// The process will never be scheduled again and will be deleted
     /// from the list of processes the the scheduler keeps track of.
     Exit,
+
+    /// Disable the preemption of the process.
+    ///
+    /// When its timeslice expires, the process is not preempted and keeps
+    /// running until it issues a [`Syscall::PreemptEnable`] system call
+    /// or it blocks.
+    PreemptDisable,
+
+    /// Enable the preemption of the process, disabled by a
+    /// [`Syscall::PreemptDisable`] system call.
+    PreemptEnable,
+
+    /// Block the process on an I/O request to a device.
+    ///
+    /// The process will be placed in the [`ProcessState::Waiting`] state
+    /// until the request completes.
+    BlockIo {
+        /// The device number.
+        device: usize,
+        /// The time that the request takes, without the fixed latency
+        /// of the device. Some scheduling algorithms can ignore the
+        /// latency of the device.
+        base_latency: usize,
+    },
+
+    /// Wait for a child process to exit.
+    ///
+    /// If the child is still alive, the process will be placed in the
+    /// [`ProcessState::Waiting`] state until the child issues a
+    /// [`Syscall::Exit`] system call. The child is then reaped and its
+    /// [`SyscallResult::Joined`] result is returned by
+    /// [`Scheduler::take_wake_result`]. If the child has already exited and
+    /// the scheduler keeps it as a zombie, the process keeps running and
+    /// receives the result right away.
+    /// Some scheduling algorithms can ignore this system call.
+    Join(
+        /// The PID of the child process.
+        Pid,
+    ),
+
+    /// Donate part of the priority of the process to another process.
+    ///
+    /// The priority of the process drops by `amount` and the priority of
+    /// the recipient rises by the same amount, within the range of an [`i8`],
+    /// until the recipient blocks or exits. A new donation to the same
+    /// recipient replaces the previous one, so an `amount` of 0 revokes it.
+    /// Some scheduling algorithms can ignore this system call.
+    Donate {
+        /// The PID of the process that receives the priority.
+        to: Pid,
+        /// The amount of priority to donate. A negative amount is handled as 0.
+        amount: i8,
+    },
+
+    /// Acquire the global kernel lock.
+    ///
+    /// Only one process can hold the lock at a time. If it is held by
+    /// another process, the process will be placed in the
+    /// [`ProcessState::Waiting`] state until it receives the lock. Waiting
+    /// for the lock is separate from waiting for events, so a
+    /// [`Syscall::Signal`] system call does not wake the process up. If the
+    /// process already holds the lock, it keeps running. Some scheduling
+    /// algorithms can ignore this system call.
+    AcquireLock,
+
+    /// Release the global kernel lock, acquired by a [`Syscall::AcquireLock`]
+    /// system call.
+    ///
+    /// The lock is handed over to the process that has been waiting for it
+    /// for the longest time, which is placed in the [`ProcessState::Ready`]
+    /// state. The lock is also released when its holder exits. If the
+    /// process does not hold the lock, nothing happens.
+    ReleaseLock,
 }
 
 /*
@@ -138,6 +437,19 @@ pub enum SyscallResult {
         /// The PID of the new process.
         Pid,
     ),
+    /// Returned for a [`Syscall::Join`] system call, once the child
+    /// has exited.
+    Joined(
+        /// The final timings of the child as a tuple of
+        /// (total, syscalls, execution).
+        (usize, usize, usize),
+    ),
+    /// Returned by [`Scheduler::take_wake_result`] for a process that was
+    /// woken up from a [`Syscall::WaitAny`] system call by one of the events.
+    Event(
+        /// The event that woke the process up.
+        usize,
+    ),
     /// The system call was successful.
     ///
     /// This is the value returned by most system calls.
@@ -149,7 +461,7 @@ pub enum SyscallResult {
 
 /// The reason that a process has stopped and the OS
 /// has called the scheduler.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StopReason {
     /// The process sent a [`Syscall`] system call.
     Syscall {
@@ -187,8 +499,12 @@ impl StopReason {
     }
 
     pub fn set_remaining(&mut self, remaining: usize) {
-        if let StopReason::Syscall { syscall, .. } = *self {
-            *self = StopReason::Syscall { syscall, remaining };
+        if let StopReason::Syscall {
+            remaining: syscall_remaining,
+            ..
+        } = self
+        {
+            *syscall_remaining = remaining;
         }
     }
 
@@ -197,6 +513,58 @@ impl StopReason {
     }
 }
 
+/// A step of a scenario run with [`Scheduler::run_script`].
+///
+/// The `remaining` fields are the time units that the running process
+/// has not used from its timeslice when it makes the system call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptOp {
+    /// Ask the scheduler for its next decision.
+    Next,
+
+    /// The running process sends a [`Syscall::Fork`] system call.
+    Fork { priority: i8, remaining: usize },
+
+    /// The running process sends a [`Syscall::Sleep`] system call.
+    Sleep { amount: usize, remaining: usize },
+
+    /// The running process sends a [`Syscall::Wait`] system call.
+    Wait { event: usize, remaining: usize },
+
+    /// The running process sends a [`Syscall::Signal`] system call.
+    Signal { event: usize, remaining: usize },
+
+    /// The running process sends a [`Syscall::Exit`] system call.
+    Exit { remaining: usize },
+
+    /// The timeslice of the running process expires.
+    Expire,
+
+    /// The running process stops for any other reason.
+    Stop(StopReason),
+}
+
+impl ScriptOp {
+    /// Returns the stop reason that the step sends to the scheduler,
+    /// or [`None`] for [`ScriptOp::Next`].
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        let (syscall, remaining) = match *self {
+            ScriptOp::Next => return None,
+            ScriptOp::Fork {
+                priority,
+                remaining,
+            } => (Syscall::Fork(priority), remaining),
+            ScriptOp::Sleep { amount, remaining } => (Syscall::Sleep(amount), remaining),
+            ScriptOp::Wait { event, remaining } => (Syscall::Wait(event), remaining),
+            ScriptOp::Signal { event, remaining } => (Syscall::Signal(event), remaining),
+            ScriptOp::Exit { remaining } => (Syscall::Exit, remaining),
+            ScriptOp::Expire => return Some(StopReason::Expired),
+            ScriptOp::Stop(ref reason) => return Some(reason.clone()),
+        };
+        Some(StopReason::Syscall { syscall, remaining })
+    }
+}
+
 /// The trait that any scheduler has to implement.
 pub trait Scheduler: Send {
     /// Returns the action that the OS has to perform next.
@@ -208,6 +576,296 @@ pub trait Scheduler: Send {
 
     /// Returns the list of processes.
     fn list(&mut self) -> Vec<&dyn Process>;
+
+    /// Returns the processes that are ready to run, in the order
+    /// in which the scheduler keeps them.
+    ///
+    /// The default implementation returns the ready processes of
+    /// [`Scheduler::list`], in the order of the list.
+    fn ready_list(&mut self) -> Vec<&dyn Process> {
+        self.list()
+            .into_iter()
+            .filter(|proc| proc.state() == ProcessState::Ready)
+            .collect()
+    }
+
+    /// Returns the processes that are waiting for an event or sleeping,
+    /// in the order in which the scheduler keeps them.
+    ///
+    /// The default implementation returns the waiting processes of
+    /// [`Scheduler::list`], in the order of the list.
+    fn waiting_list(&mut self) -> Vec<&dyn Process> {
+        self.list()
+            .into_iter()
+            .filter(|proc| matches!(proc.state(), ProcessState::Waiting { .. }))
+            .collect()
+    }
+
+    /// Returns the result of the blocking system call that the process with
+    /// PID `pid` made, once it was woken up, and forgets it.
+    ///
+    /// A process that blocks receives [`SyscallResult::Success`] from
+    /// [`Scheduler::stop`], as the result is only known when it is woken
+    /// up: the event of a [`Syscall::WaitAny`] or the timings of the child
+    /// of a [`Syscall::Join`]. The result is kept until the process makes
+    /// its next system call.
+    ///
+    /// Returns [`None`] if there is no such result. The default
+    /// implementation always returns [`None`].
+    fn take_wake_result(&mut self, _pid: Pid) -> Option<SyscallResult> {
+        None
+    }
+
+    /// Returns the time left from the timeslice of the running process,
+    /// as of the last call to [`Scheduler::next`] or [`Scheduler::stop`].
+    ///
+    /// Returns [`None`] if no process is running or if the running
+    /// process has used its whole timeslice. The default implementation
+    /// always returns [`None`].
+    fn remaining_timeslice(&self) -> Option<NonZeroUsize> {
+        None
+    }
+
+    /// Returns `true` if there is no running process and no process is
+    /// ready to run, even if some processes are waiting or sleeping.
+    fn is_idle(&mut self) -> bool {
+        !self
+            .list()
+            .iter()
+            .any(|proc| matches!(proc.state(), ProcessState::Ready | ProcessState::Running))
+    }
+
+    /// Returns the children of every process that has forked, sorted by pid.
+    ///
+    /// The processes whose parent has exited are adopted by the init
+    /// process, so the init process is the root of the tree.
+    fn process_tree(&mut self) -> HashMap<Pid, Vec<Pid>> {
+        let mut tree: HashMap<Pid, Vec<Pid>> = HashMap::new();
+        for proc in self.list() {
+            if let Some(parent) = proc.parent() {
+                tree.entry(parent).or_default().push(proc.pid());
+            }
+        }
+        for children in tree.values_mut() {
+            children.sort();
+        }
+        tree
+    }
+
+    /// Returns the share of the process with PID `pid` out of the execution
+    /// time of all the processes in [`Scheduler::list`], as a percentage.
+    ///
+    /// Returns [`None`] if there is no such process or if no process has
+    /// executed yet.
+    fn cpu_percent(&mut self, pid: Pid) -> Option<f64> {
+        let processes = self.list();
+        let total: usize = processes.iter().map(|proc| proc.timings().2).sum();
+        let proc = processes.iter().find(|proc| proc.pid() == pid)?;
+        if total == 0 {
+            return None;
+        }
+        Some(proc.timings().2 as f64 / total as f64 * 100.0)
+    }
+
+    /// Runs the steps of a scenario in order and returns the decisions
+    /// taken for every [`ScriptOp::Next`] step.
+    ///
+    /// The results of the system calls are not returned.
+    fn run_script(&mut self, ops: &[ScriptOp]) -> Vec<SchedulingDecision> {
+        let mut decisions = Vec::new();
+        for op in ops {
+            match op.stop_reason() {
+                Some(reason) => {
+                    self.stop(reason);
+                }
+                None => decisions.push(self.next()),
+            }
+        }
+        decisions
+    }
+
+    /// Calls [`Scheduler::next`] until it returns a decision other than
+    /// [`SchedulingDecision::Sleep`] or [`SchedulingDecision::Idle`], as if
+    /// the processor slept for the whole amount of every sleep.
+    ///
+    /// Returns the total time that the processor slept and the first
+    /// decision that is not a sleep or an idle time. It never returns if the scheduler keeps
+    /// the processor sleeping forever.
+    fn skip_idle(&mut self) -> (usize, SchedulingDecision) {
+        let mut skipped = 0;
+        loop {
+            match self.next() {
+                SchedulingDecision::Sleep(amount) | SchedulingDecision::Idle(amount) => {
+                    skipped += amount.get()
+                }
+                decision => return (skipped, decision),
+            }
+        }
+    }
+
+    /// Runs the simulation until the scheduler takes a decision other than
+    /// [`SchedulingDecision::Run`], [`SchedulingDecision::Sleep`] or
+    /// [`SchedulingDecision::Idle`], or for at most `max_steps` decisions,
+    /// and returns the last decision with a snapshot of every process left
+    /// in [`Scheduler::list`], sorted by pid.
+    ///
+    /// No system calls are made: every process that runs uses its whole
+    /// timeslice, and the processor sleeps for the whole amount of every
+    /// sleep. The processes are treated as CPU bound, so they never exit on
+    /// their own. It returns once the scheduler stops the simulation, e.g.
+    /// with a tick limit, when only blocked processes are left, or after
+    /// `max_steps` decisions if the processes keep running.
+    fn run_to_completion(
+        &mut self,
+        max_steps: NonZeroUsize,
+    ) -> (SchedulingDecision, Vec<ProcessSnapshot>) {
+        let mut decision = self.next();
+        for _ in 1..max_steps.get() {
+            match decision {
+                SchedulingDecision::Run { .. } => {
+                    self.stop(StopReason::Expired);
+                }
+                SchedulingDecision::Sleep(_) | SchedulingDecision::Idle(_) => {}
+                _ => break,
+            }
+            decision = self.next();
+        }
+        let mut snapshots: Vec<ProcessSnapshot> =
+            self.list().into_iter().map(ProcessSnapshot::from).collect();
+        snapshots.sort_by_key(|snapshot| snapshot.pid);
+        (decision, snapshots)
+    }
+
+    /// Advances the simulation by one step with a pseudo-random action
+    /// derived from `seed` and from the state of the scheduler, then returns
+    /// the next decision of the scheduler.
+    ///
+    /// Calling it in a loop with [`Scheduler::validate_invariants`] after
+    /// every step stresses the scheduler, and the same seeds always lead
+    /// to the same steps. Only valid actions are taken:
+    ///
+    /// * if there are no processes, the init process is forked;
+    /// * if no process is running, only [`Scheduler::next`] is called;
+    /// * otherwise the running process uses between one time unit and its
+    ///   whole remaining timeslice, then a number drawn from the seed, the
+    ///   number of processes and the remaining timeslice picks the action:
+    ///
+    /// | number | action |
+    /// |--------|--------|
+    /// | 0 | [`Syscall::Fork`] with a priority from 0 to 4, while there are less than 16 processes |
+    /// | 1 | [`Syscall::Sleep`] for 1 to 8 time units |
+    /// | 2 | [`Syscall::Wait`] for an event from 0 to 2 |
+    /// | 3 | [`Syscall::Signal`] of an event from 0 to 2 |
+    /// | 4 | [`Syscall::SignalOne`] of an event from 0 to 2 |
+    /// | 5 | [`Syscall::Exit`], one time out of four |
+    /// | 6, 7, or when 0 and 5 take no action | the timeslice expires |
+    fn fuzz_step(&mut self, seed: u64) -> SchedulingDecision {
+        let processes = self.list().len();
+        let Some(timeslice) = self.remaining_timeslice() else {
+            // Nothing is running, the init process is created if there are no processes
+            if processes == 0 {
+                self.stop(StopReason::Syscall {
+                    syscall: Syscall::Fork(0),
+                    remaining: 0,
+                });
+            }
+            return self.next();
+        };
+        // A splitmix64 generator, seeded with the seed and the state of the scheduler
+        let mut state = seed ^ ((processes as u64) << 32) ^ timeslice.get() as u64;
+        let mut random = |bound: u64| {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            (z ^ (z >> 31)) % bound
+        };
+        // The system call itself takes the last used time unit
+        let remaining = random(timeslice.get() as u64) as usize;
+        let syscall = match random(8) {
+            0 if processes < 16 => Syscall::Fork(random(5) as i8),
+            1 => Syscall::Sleep(1 + random(8) as usize),
+            2 => Syscall::Wait(random(3) as usize),
+            3 => Syscall::Signal(random(3) as usize),
+            4 => Syscall::SignalOne(random(3) as usize),
+            5 if random(4) == 0 => Syscall::Exit,
+            _ => {
+                self.stop(StopReason::Expired);
+                return self.next();
+            }
+        };
+        self.stop(StopReason::Syscall { syscall, remaining });
+        self.next()
+    }
+
+    /// Checks the consistency of the queues of the scheduler and returns
+    /// a description of the first broken invariant.
+    ///
+    /// The default implementation only checks that no pid appears more
+    /// than once in [`Scheduler::list`].
+    fn validate_invariants(&mut self) -> Result<(), String> {
+        check_unique_pids(&self.list())
+    }
+
+    /// Returns the name of the scheduling algorithm and its parameters,
+    /// like `RoundRobin(timeslice=5, min_remaining=2)`.
+    ///
+    /// The default implementation returns the name of the type.
+    fn describe(&self) -> String {
+        String::from(std::any::type_name::<Self>())
+    }
+}
+
+/// Checks that no pid appears more than once in `processes`.
+pub(crate) fn check_unique_pids(processes: &[&dyn Process]) -> Result<(), String> {
+    let mut pids = HashSet::new();
+    for proc in processes {
+        if !pids.insert(proc.pid()) {
+            return Err(format!("pid {} appears more than once", proc.pid()));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the running process is in the running state and that
+/// the remaining running time is not longer than `timeslice`.
+pub(crate) fn check_running(
+    running_process: Option<&dyn Process>,
+    remaining_running_time: usize,
+    timeslice: usize,
+) -> Result<(), String> {
+    if let Some(proc) = running_process {
+        if proc.state() != ProcessState::Running {
+            return Err(format!(
+                "the running process {} is in the {} state",
+                proc.pid(),
+                proc.state()
+            ));
+        }
+    }
+    if remaining_running_time > timeslice {
+        return Err(format!(
+            "the remaining running time {remaining_running_time} is longer than the timeslice {timeslice}"
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that every sleeping process from `wait` has a sleep amount.
+pub(crate) fn check_sleep_amounts(
+    wait: &[&dyn Process],
+    sleep_amounts: usize,
+) -> Result<(), String> {
+    let sleeping = wait
+        .iter()
+        .filter(|proc| proc.state() == ProcessState::Waiting { event: None })
+        .count();
+    if sleeping != sleep_amounts {
+        return Err(format!(
+            "{sleeping} processes are sleeping, but there are {sleep_amounts} sleep amounts"
+        ));
+    }
+    Ok(())
 }
 
 /// The state of a process.
@@ -223,9 +881,25 @@ pub enum ProcessState {
     Waiting {
         /// The event that the process is waiting for.
         ///
-        /// If the event is [`None`], the process is sleeping.
+        /// If the event is [`None`], the process is sleeping. A process that
+        /// waits for several events with [`Syscall::WaitAny`] shows the first one.
         event: Option<usize>,
     },
+
+    /// The process has exited and is kept by the scheduler until
+    /// it is reaped.
+    Exited,
+
+    /// The process was suspended by the scheduler and it is not scheduled
+    /// until it is resumed.
+    Suspended,
+
+    /// The process has used its CPU quota and it is not scheduled until
+    /// the next quota period starts.
+    Throttled {
+        /// The time at which the process becomes ready again.
+        until: usize,
+    },
 }
 
 impl Display for ProcessState {
@@ -233,6 +907,9 @@ impl Display for ProcessState {
         match self {
             ProcessState::Ready => write!(f, "READY"),
             ProcessState::Running => write!(f, "RUNNING"),
+            ProcessState::Exited => write!(f, "EXITED"),
+            ProcessState::Suspended => write!(f, "SUSPENDED"),
+            ProcessState::Throttled { until } => write!(f, "THROTTLED {}", until),
             ProcessState::Waiting { event } => {
                 if let Some(event) = event {
                     write!(f, "EVENT {}", event)
@@ -244,6 +921,80 @@ impl Display for ProcessState {
     }
 }
 
+/// Why a process from the wait queue is not ready to run.
+///
+/// Several reasons share the [`ProcessState::Waiting`] state, a sleeping
+/// process and a process that waits for a child both wait for no event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockReason {
+    /// The process made a [`Syscall::Sleep`] system call.
+    Sleeping {
+        /// The time until the process wakes up.
+        remaining: usize,
+    },
+
+    /// The process made a [`Syscall::BlockIo`] system call.
+    Io {
+        /// The time until the request completes.
+        remaining: usize,
+    },
+
+    /// The process waits for an event with [`Syscall::Wait`].
+    Event(usize),
+
+    /// The process waits for any of these events with [`Syscall::WaitAny`].
+    AnyEvent(EventSet),
+
+    /// The process waits for the child with this pid to exit.
+    Join(Pid),
+
+    /// The process waits for the kernel lock with [`Syscall::AcquireLock`].
+    Lock,
+
+    /// The process has used its CPU quota.
+    Throttled {
+        /// The time until the process becomes ready again.
+        remaining: usize,
+    },
+
+    /// The process was suspended by the scheduler.
+    Suspended,
+}
+
+/// The hints that a process gives to the scheduler about itself.
+///
+/// A forked process receives the hints of its parent. The schedulers that
+/// don't use a hint ignore it, but all of them report the hints with
+/// [`Process::extra`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProcessMeta {
+    /// The process mostly waits for I/O, so it should be treated as interactive.
+    pub io_bound: bool,
+
+    /// A name that identifies the process in the output.
+    pub label: String,
+
+    /// The time by which the process should finish.
+    pub deadline: Option<usize>,
+}
+
+impl Display for ProcessMeta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Only the hints that are set are written, separated by spaces
+        let mut hints = Vec::new();
+        if self.io_bound {
+            hints.push(String::from("IO_BOUND"));
+        }
+        if !self.label.is_empty() {
+            hints.push(format!("LABEL {}", self.label));
+        }
+        if let Some(deadline) = self.deadline {
+            hints.push(format!("DEADLINE {}", deadline));
+        }
+        write!(f, "{}", hints.join(" "))
+    }
+}
+
 /// The trait that the Process Control Block (PCB) has to implement.
 ///
 /// The PCB can be implemented with any data structure as long as
@@ -252,6 +1003,14 @@ pub trait Process: Send {
     /// Return the PID of the process.
     fn pid(&self) -> Pid;
 
+    /// Returns the PID of the process that forked this process,
+    /// the init process has no parent.
+    ///
+    /// Schedulers that don't keep track of the parents return [`None`].
+    fn parent(&self) -> Option<Pid> {
+        None
+    }
+
     /// Return the state of the process.
     fn state(&self) -> ProcessState;
 
@@ -263,4 +1022,154 @@ pub trait Process: Send {
 
     /// Returns details information
     fn extra(&self) -> String;
+
+    /// Returns how many times the process gave up the processor by
+    /// blocking, sleeping or exiting.
+    ///
+    /// Schedulers that don't count context switches return 0.
+    fn voluntary_switches(&self) -> usize {
+        0
+    }
+
+    /// Returns how many times the process was preempted because
+    /// its timeslice expired.
+    ///
+    /// Schedulers that don't count context switches return 0.
+    fn involuntary_switches(&self) -> usize {
+        0
+    }
+}
+
+/// A copy of the information that a [`Process`] reports, that can be kept
+/// after the scheduler changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessSnapshot {
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub state: ProcessState,
+    /// The (total, syscalls, execution) timings.
+    pub timings: (usize, usize, usize),
+    pub priority: i8,
+    pub extra: String,
+    pub voluntary_switches: usize,
+    pub involuntary_switches: usize,
+}
+
+impl From<&dyn Process> for ProcessSnapshot {
+    fn from(process: &dyn Process) -> Self {
+        ProcessSnapshot {
+            pid: process.pid(),
+            parent: process.parent(),
+            state: process.state(),
+            timings: process.timings(),
+            priority: process.priority(),
+            extra: process.extra(),
+            voluntary_switches: process.voluntary_switches(),
+            involuntary_switches: process.involuntary_switches(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A process that only implements the required methods.
+    struct Plain(usize, ProcessState);
+
+    impl Process for Plain {
+        fn pid(&self) -> Pid {
+            Pid::new(self.0)
+        }
+
+        fn state(&self) -> ProcessState {
+            self.1
+        }
+
+        fn timings(&self) -> (usize, usize, usize) {
+            (0, 0, 0)
+        }
+
+        fn priority(&self) -> i8 {
+            0
+        }
+
+        fn extra(&self) -> String {
+            String::new()
+        }
+    }
+
+    /// A scheduler that only implements the required methods.
+    struct Fixed(Vec<Plain>);
+
+    impl Scheduler for Fixed {
+        fn next(&mut self) -> SchedulingDecision {
+            SchedulingDecision::Done
+        }
+
+        fn stop(&mut self, _reason: StopReason) -> SyscallResult {
+            SyscallResult::Success
+        }
+
+        fn list(&mut self) -> Vec<&dyn Process> {
+            self.0.iter().map(|proc| proc as &dyn Process).collect()
+        }
+    }
+
+    #[test]
+    fn defaults_filter_the_list_by_state() {
+        let mut scheduler = Fixed(vec![
+            Plain(1, ProcessState::Waiting { event: Some(2) }),
+            Plain(2, ProcessState::Ready),
+            Plain(3, ProcessState::Running),
+            Plain(4, ProcessState::Waiting { event: None }),
+            Plain(5, ProcessState::Ready),
+        ]);
+        let pids = |list: Vec<&dyn Process>| list.iter().map(|proc| proc.pid()).collect::<Vec<_>>();
+        assert_eq!(pids(scheduler.ready_list()), [Pid::new(2), Pid::new(5)]);
+        assert_eq!(pids(scheduler.waiting_list()), [Pid::new(1), Pid::new(4)]);
+        assert_eq!(scheduler.remaining_timeslice(), None);
+        assert!(!scheduler.is_idle());
+        assert!(scheduler.list().iter().all(|proc| proc.parent().is_none()));
+        assert!(scheduler.process_tree().is_empty());
+    }
+
+    #[test]
+    fn the_default_validation_rejects_duplicate_pids() {
+        let mut scheduler = Fixed(vec![
+            Plain(1, ProcessState::Running),
+            Plain(2, ProcessState::Ready),
+        ]);
+        assert_eq!(scheduler.validate_invariants(), Ok(()));
+        scheduler
+            .0
+            .push(Plain(2, ProcessState::Waiting { event: None }));
+        assert_eq!(
+            scheduler.validate_invariants(),
+            Err(String::from("pid 2 appears more than once"))
+        );
+    }
+
+    #[test]
+    fn running_cpu_bound_processes_to_completion_stops_after_the_last_step() {
+        let mut scheduler = crate::RoundRobin::new(NonZeroUsize::new(3).unwrap(), 1);
+        scheduler.stop(StopReason::syscall(Syscall::Fork(0)));
+        let (decision, snapshots) = scheduler.run_to_completion(NonZeroUsize::new(5).unwrap());
+        // The last of the five decisions runs the process again, it is still running
+        assert!(matches!(decision, SchedulingDecision::Run { pid, .. } if pid == 1));
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].state, ProcessState::Running);
+        assert_eq!(snapshots[0].timings, (12, 0, 12));
+    }
+
+    #[test]
+    fn an_event_set_keeps_its_events_in_order() {
+        let events = EventSet::new(&[7, 3, 200, 63, 3]);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events.first(), Some(3));
+        assert_eq!(events.iter().collect::<Vec<_>>(), [3, 7, 63, 200]);
+        assert!(events.contains(63) && !events.contains(4) && !events.contains(64));
+        assert!(EventSet::default().is_empty());
+        assert_eq!(EventSet::default().first(), None);
+    }
 }
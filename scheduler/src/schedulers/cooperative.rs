@@ -0,0 +1,133 @@
+use std::num::NonZeroUsize;
+
+use crate::{
+    GenericScheduler, Pid, Process, RoundRobinPolicy, Scheduler, SchedulingDecision, StopReason,
+    SyscallResult,
+};
+
+/// A scheduler that never preempts the running process.
+///
+/// A process runs until it gives up the processor on its own: it sleeps,
+/// waits for an event or a child, blocks on an I/O request or exits. The
+/// ready processes then run in the order in which they became ready. A
+/// process that never does any of these keeps the processor forever.
+///
+/// The processes are dispatched with a timeslice of [`usize::MAX`] time
+/// units, so a [`StopReason::Expired`] is never expected. If one arrives
+/// anyway, it is ignored and the process keeps running. There is no minimum
+/// remaining timeslice either: a process that makes a system call always
+/// keeps running for the rest of its timeslice.
+pub struct Cooperative {
+    scheduler: GenericScheduler<RoundRobinPolicy>,
+}
+
+impl Cooperative {
+    pub fn new() -> Self {
+        Self {
+            scheduler: GenericScheduler::new(NonZeroUsize::MAX, 0),
+        }
+    }
+}
+
+impl Default for Cooperative {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler for Cooperative {
+    fn next(&mut self) -> SchedulingDecision {
+        self.scheduler.next()
+    }
+
+    fn stop(&mut self, reason: StopReason) -> SyscallResult {
+        match reason {
+            // The running process is never preempted
+            StopReason::Expired => SyscallResult::Success,
+            reason => self.scheduler.stop(reason),
+        }
+    }
+
+    fn list(&mut self) -> Vec<&dyn Process> {
+        self.scheduler.list()
+    }
+
+    fn ready_list(&mut self) -> Vec<&dyn Process> {
+        self.scheduler.ready_list()
+    }
+
+    fn waiting_list(&mut self) -> Vec<&dyn Process> {
+        self.scheduler.waiting_list()
+    }
+
+    fn take_wake_result(&mut self, pid: Pid) -> Option<SyscallResult> {
+        self.scheduler.take_wake_result(pid)
+    }
+
+    fn remaining_timeslice(&self) -> Option<NonZeroUsize> {
+        self.scheduler.remaining_timeslice()
+    }
+
+    fn describe(&self) -> String {
+        String::from("Cooperative")
+    }
+
+    fn is_idle(&mut self) -> bool {
+        self.scheduler.is_idle()
+    }
+
+    fn validate_invariants(&mut self) -> Result<(), String> {
+        self.scheduler.validate_invariants()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Syscall;
+
+    fn syscall(scheduler: &mut Cooperative, syscall: Syscall, remaining: usize) -> SyscallResult {
+        scheduler.stop(StopReason::Syscall { syscall, remaining })
+    }
+
+    fn runs(decision: SchedulingDecision, expected: usize) -> bool {
+        matches!(decision, SchedulingDecision::Run { pid, .. } if pid == expected)
+    }
+
+    #[test]
+    fn a_process_that_never_gives_up_the_processor_keeps_it() {
+        let mut scheduler = Cooperative::new();
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Run {
+                pid: Pid::new(1),
+                timeslice: NonZeroUsize::MAX,
+            }
+        );
+        let remaining = usize::MAX - 1;
+        syscall(&mut scheduler, Syscall::Fork(0), remaining);
+        // The child is ready, but the parent is never preempted
+        for _ in 0..1000 {
+            assert!(runs(scheduler.next(), 1));
+        }
+        assert_eq!(scheduler.ready_list().len(), 1);
+        syscall(&mut scheduler, Syscall::Sleep(10), remaining - 1);
+        assert!(runs(scheduler.next(), 2));
+    }
+
+    #[test]
+    fn an_expired_timeslice_is_ignored() {
+        let mut scheduler = Cooperative::new();
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(runs(scheduler.next(), 1));
+        syscall(&mut scheduler, Syscall::Fork(0), usize::MAX - 1);
+        assert_eq!(scheduler.stop(StopReason::Expired), SyscallResult::Success);
+        assert!(runs(scheduler.next(), 1));
+        let running = scheduler.list().into_iter().find(|proc| proc.pid() == 1);
+        assert_eq!(
+            running.map(|proc| proc.state()),
+            Some(crate::ProcessState::Running)
+        );
+    }
+}
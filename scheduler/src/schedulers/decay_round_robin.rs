@@ -0,0 +1,457 @@
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+
+use crate::scheduler::{check_running, check_sleep_amounts, check_unique_pids};
+use crate::{Pid, Process, ProcessMeta, ProcessState, Scheduler, Syscall, SyscallResult};
+
+pub struct ProcessInfo {
+    pid: Pid,
+    parent: Option<Pid>,
+    state: ProcessState,
+    timings: (usize, usize, usize),
+    priority: i8,
+    meta: ProcessMeta,
+    expiry_streak: i32, // how many times in a row the process used its whole quanta
+    preempt_disabled: bool,
+}
+
+/// A round robin scheduler that shrinks the timeslice of CPU bound processes.
+///
+/// Every consecutive expiry of a process multiplies its next timeslice by
+/// the decay factor, down to a floor. The process gets the base timeslice
+/// back as soon as it blocks.
+pub struct DecayRoundRobin {
+    base: NonZeroUsize,
+    floor: NonZeroUsize,
+    decay: f64,
+    ready: Vec<ProcessInfo>, // ready queue
+    wait: Vec<ProcessInfo>,  // wait queue
+    pid_counter: usize,
+    free_pids: VecDeque<Pid>, // pids of exited processes that can be reused
+    reuse_pids: bool,         // give the pids of exited processes to new ones
+    running_process: Option<ProcessInfo>,
+    remaining_running_time: usize,
+    init: bool,
+    sleep_amounts: Vec<usize>,
+    sleep: usize,
+}
+
+impl DecayRoundRobin {
+    /// * `base` - the timeslice of a process that has not expired yet
+    /// * `floor` - the minimum timeslice that a process can receive
+    /// * `decay` - the factor that the timeslice is multiplied with for
+    ///   every consecutive expiry, between 0 and 1
+    pub fn new(base: NonZeroUsize, floor: NonZeroUsize, decay: f64) -> Self {
+        Self {
+            base,
+            floor,
+            decay,
+            ready: Vec::new(),
+            wait: Vec::new(),
+            pid_counter: 1,
+            free_pids: VecDeque::new(),
+            reuse_pids: false,
+            running_process: None,
+            remaining_running_time: 0,
+            init: false,
+            sleep_amounts: Vec::new(),
+            sleep: 0,
+        }
+    }
+    /// Give the pids of exited processes to new processes, the oldest
+    /// one first, before new pids are generated.
+    ///
+    /// By default, every process receives a new pid. Pid 1 is never reused.
+    pub fn with_pid_reuse(mut self, reuse_pids: bool) -> Self {
+        self.reuse_pids = reuse_pids;
+        self
+    }
+    /// Replace the hints of the process with the given pid, the children
+    /// that it forks afterwards receive the new hints.
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn set_meta(&mut self, pid: Pid, meta: ProcessMeta) -> bool {
+        // Search the process in all the queues
+        match self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            Some(proc) => {
+                proc.meta = meta;
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn generate_pid(&mut self) -> Pid {
+        // Reuse the pid of an exited process if possible
+        while let Some(pid) = self.free_pids.pop_front() {
+            if !self.is_pid_used(pid) {
+                return pid;
+            }
+        }
+        // Generate a new PID, skipping the pids that are still in use
+        loop {
+            let new_pid = Pid::new(self.pid_counter);
+            // Wrap around on overflow, pid 1 is reserved for the init process
+            self.pid_counter = self.pid_counter.checked_add(1).unwrap_or(2);
+            if !self.is_pid_used(new_pid) {
+                return new_pid;
+            }
+        }
+    }
+    fn is_pid_used(&self, pid: Pid) -> bool {
+        // Check if any process from the scheduler has this pid
+        self.ready
+            .iter()
+            .chain(self.wait.iter())
+            .chain(self.running_process.iter())
+            .any(|proc| proc.pid == pid)
+    }
+    fn timeslice(&self, proc: &ProcessInfo) -> NonZeroUsize {
+        // Decay the base timeslice once for every consecutive expiry
+        let timeslice = (self.base.get() as f64 * self.decay.powi(proc.expiry_streak)) as usize;
+        NonZeroUsize::new(timeslice)
+            .unwrap_or(self.floor)
+            .max(self.floor)
+    }
+    fn dispatch(&mut self, mut proc: ProcessInfo) -> crate::SchedulingDecision {
+        let timeslice = self.timeslice(&proc);
+        proc.state = ProcessState::Running;
+        let pid = proc.pid;
+        self.running_process = Some(proc);
+        self.remaining_running_time = timeslice.into();
+        crate::SchedulingDecision::Run { pid, timeslice }
+    }
+    pub fn increase_timings(&mut self, amount: usize) {
+        // Update timings for all processes and sleep amounts
+        for proc in &mut self.ready {
+            proc.timings.0 += amount;
+        }
+        for proc in &mut self.wait {
+            proc.timings.0 += amount;
+        }
+        for sleep in &mut self.sleep_amounts {
+            *sleep = sleep.saturating_sub(amount);
+        }
+        // Take the awakened processes from the queue and make them ready
+        // (the sleep amounts are kept in the order of the sleeping processes)
+        let mut sleeper = 0;
+        let mut index = 0;
+        while index < self.wait.len() {
+            if let ProcessState::Waiting { event: None } = self.wait[index].state {
+                if self.sleep_amounts[sleeper] == 0 {
+                    self.sleep_amounts.remove(sleeper);
+                    let mut proc = self.wait.remove(index);
+                    proc.state = ProcessState::Ready;
+                    self.ready.push(proc);
+                    continue;
+                }
+                sleeper += 1;
+            }
+            index += 1;
+        }
+    }
+    fn update_running(&mut self, remaining: usize) -> Option<ProcessInfo> {
+        // Update the timings of the running process after a syscall
+        let mut running_process = self.running_process.take()?;
+        let elapsed = self.remaining_running_time - remaining;
+        running_process.timings.0 += elapsed;
+        running_process.timings.1 += 1;
+        running_process.timings.2 += elapsed - 1; // - 1 (the syscall)
+        Some(running_process)
+    }
+}
+
+impl Process for ProcessInfo {
+    fn pid(&self) -> crate::Pid {
+        self.pid
+    }
+    fn parent(&self) -> Option<Pid> {
+        self.parent
+    }
+    fn state(&self) -> ProcessState {
+        self.state
+    }
+    fn timings(&self) -> (usize, usize, usize) {
+        self.timings
+    }
+    fn priority(&self) -> i8 {
+        self.priority
+    }
+    fn extra(&self) -> String {
+        self.meta.to_string()
+    }
+}
+
+impl Scheduler for DecayRoundRobin {
+    fn next(&mut self) -> crate::SchedulingDecision {
+        // Increase all timings after a sleep (if 0, it will increase with 0)
+        self.increase_timings(self.sleep);
+        self.sleep = 0;
+
+        if let Some(mut running_process) = self.running_process.take() {
+            if running_process.preempt_disabled && self.remaining_running_time == 0 {
+                // A process that can't be preempted gets a new quanta
+                return self.dispatch(running_process);
+            }
+            if self.remaining_running_time > 0 {
+                // Reschedule the running process for the rest of its quanta
+                let pid = running_process.pid;
+                self.running_process = Some(running_process);
+                return crate::SchedulingDecision::Run {
+                    pid,
+                    timeslice: NonZeroUsize::new(self.remaining_running_time).unwrap(),
+                };
+            }
+            // The quanta was used, place it back in the ready queue
+            running_process.state = ProcessState::Ready;
+            self.ready.push(running_process);
+        }
+
+        // Check for panic (if the process with pid 1 has exited)
+        if self.init && !(self.ready.is_empty() && self.wait.is_empty()) {
+            self.init = false;
+            return crate::SchedulingDecision::Panic;
+        }
+        if !self.ready.is_empty() {
+            let proc = self.ready.remove(0);
+            return self.dispatch(proc);
+        }
+        if self.wait.is_empty() {
+            return crate::SchedulingDecision::Done;
+        }
+        // Check for deadlock (there are only processes that wait for a signal in the wait queue)
+        if self.sleep_amounts.is_empty() {
+            return crate::SchedulingDecision::Deadlock;
+        }
+        // Sleep the processor until the first sleeping process wakes up
+        let min_amount = self.sleep_amounts.iter().copied().min().unwrap();
+        self.sleep = min_amount;
+        match NonZeroUsize::new(min_amount) {
+            Some(amount) => crate::SchedulingDecision::Sleep(amount),
+            None => self.next(),
+        }
+    }
+
+    fn stop(&mut self, reason: crate::StopReason) -> crate::SyscallResult {
+        match reason {
+            crate::StopReason::Syscall { syscall, remaining } => match syscall {
+                Syscall::Fork(priority) => {
+                    // Increase all total timings
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    // Generate a new process
+                    let new_pid = self.generate_pid();
+                    let new_process = ProcessInfo {
+                        pid: new_pid,
+                        // The running process is the parent (none for the init process)
+                        parent: self.running_process.as_ref().map(|proc| proc.pid),
+                        state: ProcessState::Ready,
+                        timings: (0, 0, 0),
+                        priority,
+                        // The child receives the hints of its parent
+                        meta: self
+                            .running_process
+                            .as_ref()
+                            .map(|proc| proc.meta.clone())
+                            .unwrap_or_default(),
+                        expiry_streak: 0,
+                        preempt_disabled: false,
+                    };
+                    self.ready.push(new_process);
+                    if let Some(running_process) = self.update_running(remaining) {
+                        // Save the remaining time for the running process and regain ownership
+                        self.remaining_running_time = remaining;
+                        self.running_process = Some(running_process);
+                    }
+                    SyscallResult::Pid(new_pid)
+                }
+                // There are no device latencies, an I/O request is a sleep
+                Syscall::Sleep(amount)
+                | Syscall::BlockIo {
+                    base_latency: amount,
+                    ..
+                } => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    if let Some(mut running_process) = self.update_running(remaining) {
+                        // The process blocked, it gets the base timeslice back
+                        running_process.expiry_streak = 0;
+                        if amount == 0 {
+                            // A zero length sleep only yields, the process is ready again
+                            running_process.state = ProcessState::Ready;
+                            self.ready.push(running_process);
+                        } else {
+                            running_process.state = ProcessState::Waiting { event: None };
+                            self.wait.push(running_process);
+                            self.sleep_amounts.push(amount);
+                        }
+                    }
+                    self.remaining_running_time = 0;
+                    SyscallResult::Success
+                }
+                Syscall::Wait(e) => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    if let Some(mut running_process) = self.update_running(remaining) {
+                        // The process blocked, it gets the base timeslice back
+                        running_process.expiry_streak = 0;
+                        running_process.state = ProcessState::Waiting { event: Some(e) };
+                        self.wait.push(running_process);
+                    }
+                    self.remaining_running_time = 0;
+                    SyscallResult::Success
+                }
+                Syscall::Signal(e) => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    // Awaken all the processes that wait for the 'e' event, keeping their order
+                    let (awakened, waiting): (Vec<_>, Vec<_>) = self
+                        .wait
+                        .drain(..)
+                        .partition(|proc| proc.state == ProcessState::Waiting { event: Some(e) });
+                    self.wait = waiting;
+                    for mut proc in awakened {
+                        proc.state = ProcessState::Ready;
+                        self.ready.push(proc);
+                    }
+                    if let Some(running_process) = self.update_running(remaining) {
+                        self.remaining_running_time = remaining;
+                        self.running_process = Some(running_process);
+                    }
+                    SyscallResult::Success
+                }
+                Syscall::SignalOne(e) => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    // Awaken the process that has been waiting for the 'e' event for the longest time
+                    if let Some(index) = self
+                        .wait
+                        .iter()
+                        .position(|proc| proc.state == ProcessState::Waiting { event: Some(e) })
+                    {
+                        let mut proc = self.wait.remove(index);
+                        proc.state = ProcessState::Ready;
+                        self.ready.push(proc);
+                    }
+                    if let Some(running_process) = self.update_running(remaining) {
+                        self.remaining_running_time = remaining;
+                        self.running_process = Some(running_process);
+                    }
+                    SyscallResult::Success
+                }
+                Syscall::PreemptDisable | Syscall::PreemptEnable => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    if let Some(mut running_process) = self.update_running(remaining) {
+                        running_process.preempt_disabled = syscall == Syscall::PreemptDisable;
+                        self.remaining_running_time = remaining;
+                        self.running_process = Some(running_process);
+                    }
+                    SyscallResult::Success
+                }
+                // Joins, waits for several events, deferred forks, donations and the
+                // kernel lock are not supported, the process keeps running
+                Syscall::Join(_)
+                | Syscall::WaitAny(_)
+                | Syscall::ForkDeferred(_)
+                | Syscall::Donate { .. }
+                | Syscall::AcquireLock
+                | Syscall::ReleaseLock => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    if let Some(running_process) = self.update_running(remaining) {
+                        self.remaining_running_time = remaining;
+                        self.running_process = Some(running_process);
+                    }
+                    SyscallResult::Success
+                }
+                Syscall::Exit => {
+                    self.increase_timings(self.remaining_running_time.saturating_sub(remaining));
+                    // Verify if process with pid 1 has exited
+                    if let Some(running_process) = self.running_process.take() {
+                        if running_process.pid == 1 {
+                            self.init = true;
+                        } else {
+                            if self.reuse_pids {
+                                // Its pid can be given to a new process
+                                self.free_pids.push_back(running_process.pid);
+                            }
+                            // Its children are adopted by the init process
+                            for proc in self.ready.iter_mut().chain(self.wait.iter_mut()) {
+                                if proc.parent == Some(running_process.pid) {
+                                    proc.parent = Some(Pid::new(1));
+                                }
+                            }
+                        }
+                    }
+                    self.remaining_running_time = 0;
+                    SyscallResult::Success
+                }
+            },
+            crate::StopReason::Expired => {
+                self.increase_timings(self.remaining_running_time);
+                if let Some(mut running_process) = self.running_process.take() {
+                    running_process.timings.0 += self.remaining_running_time;
+                    running_process.timings.2 += self.remaining_running_time;
+                    // The process used its whole quanta, its next one is shorter
+                    running_process.expiry_streak = running_process.expiry_streak.saturating_add(1);
+                    if running_process.preempt_disabled {
+                        // The process can't be preempted, it keeps running
+                        self.running_process = Some(running_process);
+                    } else {
+                        running_process.state = ProcessState::Ready;
+                        self.ready.push(running_process);
+                    }
+                }
+                self.remaining_running_time = 0;
+                SyscallResult::Success
+            }
+        }
+    }
+
+    fn list(&mut self) -> Vec<&dyn Process> {
+        let mut list: Vec<&dyn Process> = Vec::new();
+        for i in &self.ready {
+            list.push(i)
+        }
+        for i in &self.wait {
+            list.push(i)
+        }
+        if let Some(x) = &self.running_process {
+            list.push(x);
+        }
+        list
+    }
+
+    fn ready_list(&mut self) -> Vec<&dyn Process> {
+        self.ready.iter().map(|proc| proc as &dyn Process).collect()
+    }
+
+    fn waiting_list(&mut self) -> Vec<&dyn Process> {
+        self.wait.iter().map(|proc| proc as &dyn Process).collect()
+    }
+
+    fn remaining_timeslice(&self) -> Option<NonZeroUsize> {
+        self.running_process
+            .as_ref()
+            .and_then(|_| NonZeroUsize::new(self.remaining_running_time))
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "DecayRoundRobin(base={}, floor={}, decay={})",
+            self.base, self.floor, self.decay
+        )
+    }
+
+    fn validate_invariants(&mut self) -> Result<(), String> {
+        check_unique_pids(&self.list())?;
+        let sleepers = self.sleep_amounts.len();
+        check_sleep_amounts(&self.waiting_list(), sleepers)?;
+        check_running(
+            self.running_process
+                .as_ref()
+                .map(|proc| proc as &dyn Process),
+            self.remaining_running_time,
+            self.base.max(self.floor).get(),
+        )
+    }
+}
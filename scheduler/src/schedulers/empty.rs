@@ -14,4 +14,8 @@ impl Scheduler for Empty {
     fn list(&mut self) -> Vec<&dyn crate::Process> {
         unimplemented!()
     }
+
+    fn describe(&self) -> String {
+        String::from("Empty")
+    }
 }
@@ -0,0 +1,3729 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+
+use crate::scheduler::{check_running, check_unique_pids};
+use crate::{
+    AloneExpiry, BlockReason, EventSet, IdleReason, Pid, Process, ProcessMeta, ProcessState,
+    Scheduler, Syscall, SyscallResult, TimeUnit, WakePolicy,
+};
+
+/// A sleeping process: its wake tick, the order in which it went to sleep
+/// and its pid. The heap of sleepers returns the earliest wake tick first.
+type Sleeper = Reverse<(usize, usize, Pid)>;
+
+/// A function that can change the decisions of the scheduler. Every clone
+/// of the scheduler receives its own copy of the function.
+type DecisionFilter = Box<dyn FilterFn>;
+
+trait FilterFn: FnMut(crate::SchedulingDecision) -> crate::SchedulingDecision + Send {
+    fn clone_box(&self) -> DecisionFilter;
+}
+
+impl<F> FilterFn for F
+where
+    F: FnMut(crate::SchedulingDecision) -> crate::SchedulingDecision + Clone + Send + 'static,
+{
+    fn clone_box(&self) -> DecisionFilter {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for DecisionFilter {
+    fn clone(&self) -> Self {
+        // The box itself is a filter too, clone the filter that it holds
+        (**self).clone_box()
+    }
+}
+
+/// The process control block of a [`GenericScheduler`].
+#[derive(Clone)]
+pub struct ProcessInfo {
+    pid: Pid,
+    parent: Option<Pid>,
+    state: ProcessState,
+    timings: (usize, usize, usize),
+    priority: i8,
+    default_priority: i8,
+    meta: ProcessMeta,
+    preempt_disabled: bool,
+    burst: usize, // the time the process has been running since it last blocked or expired
+    io_latency: Option<usize>, // the latency of the I/O request that the process waits for
+    quota: Option<NonZeroUsize>, // the execution time allowed in every quota period
+    timeslice: Option<NonZeroUsize>, // the timeslice used instead of the one of the scheduler
+    execution_limit: Option<NonZeroUsize>, // the execution time at which it is terminated
+    period_start: usize, // the execution time at the start of the quota period
+    joining: Option<Pid>, // the child that the process waits for
+    locking: bool, // whether the process waits for the kernel lock
+    events: EventSet, // the events that the process waits for with a WaitAny
+    wake_result: Option<SyscallResult>, // the result of the system call that blocked the process
+    ready_at: usize, // the busy time at which the process became ready
+    queued_since: Option<usize>, // the time since which the process waits in a queue
+    pending_forks: Vec<i8>, // the priorities of the children to create when the process runs again
+    voluntary_switches: usize, // the number of times the process blocked, slept or exited
+    involuntary_switches: usize, // the number of times the process was preempted
+    created: usize, // the time at which the process was created
+    first_run: Option<usize>, // the time at which the process was dispatched for the first time
+    exited: Option<usize>, // the time at which the process exited
+    donation: i16, // the priority received from other processes minus the donated one
+}
+
+impl ProcessInfo {
+    /// Returns the time the process has been running since its last CPU burst ended.
+    pub fn burst(&self) -> usize {
+        self.burst
+    }
+    /// Returns the priority of the process, without the priority that it
+    /// received from other processes or donated to them.
+    pub fn base_priority(&self) -> i8 {
+        self.priority
+    }
+    /// Returns the priority that the process was created with, or the one
+    /// given by [`GenericScheduler::set_priority`].
+    pub fn default_priority(&self) -> i8 {
+        self.default_priority
+    }
+    /// Change the base priority of the process, its default priority stays the same.
+    pub fn set_base_priority(&mut self, priority: i8) {
+        self.priority = priority;
+    }
+    /// Returns the hints of the process.
+    pub fn meta(&self) -> &ProcessMeta {
+        &self.meta
+    }
+    fn new(pid: Pid, priority: i8, created: usize) -> Self {
+        Self {
+            pid,
+            parent: None,
+            state: ProcessState::Ready,
+            timings: (0, 0, 0),
+            priority,
+            default_priority: priority,
+            meta: ProcessMeta::default(),
+            preempt_disabled: false,
+            burst: 0,
+            io_latency: None,
+            quota: None,
+            timeslice: None,
+            execution_limit: None,
+            period_start: 0,
+            joining: None,
+            locking: false,
+            events: EventSet::default(),
+            wake_result: None,
+            ready_at: 0,
+            queued_since: Some(created),
+            pending_forks: Vec::new(),
+            voluntary_switches: 0,
+            involuntary_switches: 0,
+            created,
+            first_run: None,
+            exited: None,
+            donation: 0,
+        }
+    }
+    fn effective_priority(&self) -> i8 {
+        // The donations can't take the priority out of the range of an i8
+        (i16::from(self.priority) + self.donation).clamp(i8::MIN.into(), i8::MAX.into()) as i8
+    }
+    fn slowdown(&self) -> Option<f64> {
+        // The turnaround time divided by the execution time
+        let turnaround = self.exited? - self.created;
+        if self.timings.2 == 0 {
+            return None;
+        }
+        Some(turnaround as f64 / self.timings.2 as f64)
+    }
+    fn enqueue(&mut self, tick: usize) {
+        // Moving between queues keeps the time since the process left the processor
+        self.queued_since.get_or_insert(tick);
+    }
+    fn dequeue(&mut self, tick: usize) {
+        // Add the time spent in the queues to the total time
+        if let Some(since) = self.queued_since.take() {
+            self.timings.0 += tick - since;
+        }
+    }
+    fn settle(&mut self, tick: usize) {
+        // Add the time spent in the queues so far, the process stays queued
+        if let Some(since) = self.queued_since.as_mut() {
+            self.timings.0 += tick - *since;
+            *since = tick;
+        }
+    }
+    fn timings_at(&self, tick: usize) -> (usize, usize, usize) {
+        // The time spent in the queues is only added to the total when the process leaves them
+        let queued = self.queued_since.map_or(0, |since| tick - since);
+        (self.timings.0 + queued, self.timings.1, self.timings.2)
+    }
+    fn waits_for(&self, event: usize) -> bool {
+        self.state == ProcessState::Waiting { event: Some(event) } || self.events.contains(event)
+    }
+    fn wake(&mut self, event: usize) {
+        self.state = ProcessState::Ready;
+        // The event is the result of the WaitAny of the process
+        if !self.events.is_empty() {
+            self.events = EventSet::default();
+            self.wake_result = Some(SyscallResult::Event(event));
+        }
+    }
+}
+
+/// Decides which ready process a [`GenericScheduler`] runs next.
+pub trait SelectionPolicy: Send {
+    /// Returns the index in `ready` of the process that runs next.
+    ///
+    /// The ready processes are in the order in which they became ready, or
+    /// in which their priority last changed, `ready` is never empty.
+    fn pick(&mut self, ready: &[ProcessInfo]) -> usize;
+
+    /// Called every time a CPU burst of `proc` ends, when the process
+    /// blocks, exits or uses its whole quanta.
+    ///
+    /// * `burst` - the time the process was running during the burst
+    fn burst_completed(&mut self, _proc: &ProcessInfo, _burst: usize) {}
+
+    /// Returns the priority that a process receives when it asks for
+    /// `priority`, when it is created or its priority is set.
+    fn clamp_priority(&self, priority: i8) -> i8 {
+        priority
+    }
+
+    /// Returns `true` if a process from `ready` has to run before `running`,
+    /// checked at the end of every interval of a timeslice, as set by
+    /// [`GenericScheduler::with_preempt_check_interval`].
+    fn preempts(&mut self, _running: &ProcessInfo, _ready: &[ProcessInfo]) -> bool {
+        false
+    }
+
+    /// Called every time `proc` is dispatched, before it runs.
+    fn dispatched(&mut self, _proc: &mut ProcessInfo) {}
+
+    /// Called every time `proc` uses its whole timeslice and can be preempted.
+    fn expired(&mut self, _proc: &mut ProcessInfo) {}
+
+    /// Called every time `proc` makes a system call, before it exits or blocks.
+    fn system_call(&mut self, _proc: &mut ProcessInfo) {}
+
+    /// Called every time `proc` wakes up from a sleep or from a wait for an
+    /// event, before it is placed in the ready queue.
+    fn woken(&mut self, _proc: &mut ProcessInfo) {}
+}
+
+/// The statistics of the processes that have the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LevelStat {
+    /// The number of processes.
+    pub processes: usize,
+    /// The total execution time of the processes.
+    pub execution_ticks: usize,
+    /// The total time that the processes waited in the queues.
+    pub wait_ticks: usize,
+}
+
+/// A scheduler that keeps the queues, the timings and the sleeping processes
+/// and asks its [`SelectionPolicy`] which ready process runs next.
+///
+/// Cloning the scheduler creates an independent copy of all its
+/// processes and queues, that can continue the simulation separately.
+#[derive(Clone)]
+pub struct GenericScheduler<P: SelectionPolicy> {
+    policy: P, // picks the ready process that runs next
+    timeslice: NonZeroUsize,
+    minimum_remaining_timeslice: usize,
+    ready: Vec<ProcessInfo>,              // ready queue
+    wait: Vec<ProcessInfo>,               // wait queue
+    suspended: Vec<ProcessInfo>,          // processes that are not scheduled until they are resumed
+    pid_counter: usize,                   // used to increase pids
+    free_pids: VecDeque<Pid>,             // pids of exited processes that can be reused
+    spawned: usize,                       // the number of processes that received a pid
+    exited: usize,                        // the number of processes that exited
+    running_process: Option<ProcessInfo>, // the currently running process
+    remaining_running_time: usize,        // remaining running time
+    slice_left: usize,                    // the time left from the timeslice after the interval
+    check_interval: Option<NonZeroUsize>, // the interval at which the preemption is checked
+    init: bool,                           // to check if process with pid 1 exited
+    sleepers: BinaryHeap<Sleeper>,        // keep track of the sleeping processes
+    sleep_counter: usize,                 // used to order the sleeping processes
+    sleep_granularity: usize,             // the sleep amounts are rounded up to a multiple of it
+    sleep: usize,                         // increase the timings when a process wakes up from sleep
+    switch_cost: usize,                   // time charged for every context switch
+    overhead_ticks: usize,                // total time spent in context switches
+    idle_ticks: usize,                    // total time the processor slept
+    dispatch_latency: usize,              // time charged every time a process is dispatched
+    dispatch_ticks: usize,                // total time spent dispatching processes
+    syscall_cost: usize,                  // time charged for every system call
+    syscall_ticks: usize,                 // total time spent handling system calls
+    strict_done: bool,                    // only finish once all the processes have exited
+    last_pid: Option<Pid>,                // the last process that was scheduled
+    bursts: Vec<usize>,                   // the number of bursts for each length bucket
+    target_latency: Option<NonZeroUsize>, // the time in which all the ready processes should run
+    min_slice: NonZeroUsize,              // the minimum timeslice when using a target latency
+    devices: HashMap<usize, usize>,       // the fixed latency of every device
+    device_wait_ticks: usize,             // total time spent by processes waiting for devices
+    idle_reason: Option<IdleReason>,      // why the last decision did not run a process
+    tick_jitter: usize,                   // the maximum error of the elapsed time of a system call
+    current_tick: usize,                  // the time elapsed since the start
+    recent_ticks: VecDeque<bool>,         // whether the processor was busy in the last ticks
+    utilization_window: NonZeroUsize,     // the number of ticks kept in recent_ticks
+    time_unit: TimeUnit,                  // the unit of the scaled times
+    max_ticks: Option<usize>,             // the time after which the simulation is done
+    quota_period: Option<NonZeroUsize>,   // the interval at which the CPU quotas are refilled
+    quota_window: usize,                  // the current quota period
+    quota_share: f64,                     // the share of a quota given to a child
+    zombies: Vec<ProcessInfo>,            // exited processes that were not reaped yet
+    timed_out: Vec<Pid>,                  // the processes terminated by their execution limit
+    keep_zombies: bool,                   // keep all the exited processes until they are reaped
+    reuse_pids: bool,                     // give the pids of exited processes to new ones
+    wake_policy: WakePolicy,              // the waiter that a SignalOne wakes up
+    starvation_limit: Option<usize>,      // the longest time a ready process can wait to run
+    init_priority: Option<i8>,            // the priority of the init process, instead of its fork
+    max_ready_depth: (usize, usize),      // the most ready processes and when they were reached
+    child_runs_first: bool,               // run a forked child before its parent
+    alone_expiry: AloneExpiry,            // what happens when a process expires alone
+    min_run: usize,                       // the shortest burst that can be preempted
+    event_check: bool,                    // keep track of the waited and signaled events
+    waited_events: BTreeSet<usize>,       // the events that processes waited for
+    signaled_events: BTreeSet<usize>,     // the events that were signaled
+    filter: Option<DecisionFilter>,       // changes the decisions of next
+    lock_holder: Option<Pid>,             // the process that holds the kernel lock
+    interrupts: usize,                    // the number of processes preempted by an interrupt
+    donations: Vec<(Pid, Pid, i8)>,       // the priority donated by a process to another one
+}
+impl<P: SelectionPolicy + Default> GenericScheduler<P> {
+    /// * `timeslice` - the time quanta that a process can run before it is preempted
+    /// * `minimum_remaining_timeslice` - the minimum remaining quanta for which a
+    ///   process is scheduled again after a system call
+    pub fn new(timeslice: NonZeroUsize, minimum_remaining_timeslice: usize) -> Self {
+        Self::from_policy(P::default(), timeslice, minimum_remaining_timeslice)
+    }
+    /// Returns a scheduler that runs the processes one time unit at a time,
+    /// the same as `new` with a timeslice of 1 and a minimum remaining
+    /// timeslice of 1.
+    pub fn single_step() -> Self {
+        Self::new(NonZeroUsize::MIN, 1)
+    }
+}
+
+impl<P: SelectionPolicy> GenericScheduler<P> {
+    /// The same as [`GenericScheduler::new`], with a policy that can't be
+    /// created with [`Default`].
+    pub fn from_policy(
+        policy: P,
+        timeslice: NonZeroUsize,
+        minimum_remaining_timeslice: usize,
+    ) -> Self {
+        Self {
+            policy,
+            timeslice,
+            minimum_remaining_timeslice,
+            ready: Vec::new(),
+            wait: Vec::new(),
+            suspended: Vec::new(),
+            pid_counter: 1,
+            free_pids: VecDeque::new(),
+            spawned: 0,
+            exited: 0,
+            running_process: None,
+            remaining_running_time: timeslice.into(),
+            slice_left: 0,
+            check_interval: None,
+            init: false,
+            sleepers: BinaryHeap::new(),
+            sleep_counter: 0,
+            sleep_granularity: 1,
+            sleep: 0,
+            switch_cost: 0,
+            overhead_ticks: 0,
+            idle_ticks: 0,
+            dispatch_latency: 0,
+            dispatch_ticks: 0,
+            syscall_cost: 0,
+            syscall_ticks: 0,
+            strict_done: false,
+            last_pid: None,
+            bursts: Vec::new(),
+            target_latency: None,
+            min_slice: timeslice,
+            devices: HashMap::new(),
+            device_wait_ticks: 0,
+            idle_reason: None,
+            tick_jitter: 0,
+            current_tick: 0,
+            recent_ticks: VecDeque::new(),
+            utilization_window: NonZeroUsize::new(100).unwrap(),
+            time_unit: TimeUnit::Ticks,
+            max_ticks: None,
+            quota_period: None,
+            quota_window: 0,
+            quota_share: 0.0,
+            zombies: Vec::new(),
+            timed_out: Vec::new(),
+            keep_zombies: false,
+            reuse_pids: false,
+            wake_policy: WakePolicy::LongestWaiting,
+            starvation_limit: None,
+            init_priority: None,
+            max_ready_depth: (0, 0),
+            child_runs_first: false,
+            alone_expiry: AloneExpiry::Requeue,
+            min_run: 0,
+            event_check: false,
+            waited_events: BTreeSet::new(),
+            signaled_events: BTreeSet::new(),
+            filter: None,
+            lock_holder: None,
+            interrupts: 0,
+            donations: Vec::new(),
+        }
+    }
+    /// Returns the selection policy.
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+    /// Returns the selection policy, to change its settings.
+    pub fn policy_mut(&mut self) -> &mut P {
+        &mut self.policy
+    }
+    /// Create the init process (pid 1) with `priority` and place it in the
+    /// ready queue, so that the first `next` runs it without a fork of the
+    /// first process.
+    ///
+    /// The priority is handled like the one of a [`crate::Syscall::Fork`], so
+    /// the builders that change it should be called before this one.
+    pub fn with_init(mut self, priority: i8) -> Self {
+        let pid = self.generate_pid();
+        let priority = self
+            .policy
+            .clamp_priority(self.fork_priority(pid, priority));
+        let init = ProcessInfo::new(pid, priority, self.current_tick);
+        self.push_ready(init);
+        self
+    }
+    /// Charge `switch_cost` time units every time the running process changes.
+    ///
+    /// The cost advances the timings of all the processes, but it is not
+    /// counted as execution time for any of them.
+    pub fn with_switch_cost(mut self, switch_cost: usize) -> Self {
+        self.switch_cost = switch_cost;
+        self
+    }
+    /// Charge `dispatch_latency` time units every time a process leaves the
+    /// ready queue to run, even if it ran before.
+    ///
+    /// Like the switch cost, the latency advances the timings of all the
+    /// processes without being counted as execution time. The process still
+    /// receives its whole timeslice after it is dispatched.
+    pub fn with_dispatch_latency(mut self, dispatch_latency: usize) -> Self {
+        self.dispatch_latency = dispatch_latency;
+        self
+    }
+    /// Charge `syscall_cost` time units to the running process for every
+    /// system call that it makes.
+    ///
+    /// The cost is added to the total time of the process and advances the
+    /// timings of all the other processes, but it is not counted as execution
+    /// time and it does not use the timeslice of the process.
+    pub fn with_syscall_cost(mut self, syscall_cost: usize) -> Self {
+        self.syscall_cost = syscall_cost;
+        self
+    }
+    /// Compute the timeslice of every dispatched process from the load, instead
+    /// of using a fixed one.
+    ///
+    /// A process receives `max(min_slice, target_latency / runnable)`, where
+    /// `runnable` counts the ready processes together with the dispatched one,
+    /// so that all of them get to run within `target_latency` time units.
+    pub fn with_target_latency(
+        mut self,
+        target_latency: NonZeroUsize,
+        min_slice: NonZeroUsize,
+    ) -> Self {
+        self.target_latency = Some(target_latency);
+        self.min_slice = min_slice;
+        self
+    }
+    /// Add `latency` time units to every I/O request made to `device`,
+    /// on top of the base latency of the request.
+    pub fn with_device_latency(mut self, device: usize, latency: usize) -> Self {
+        self.devices.insert(device, latency);
+        self
+    }
+    /// Returns the total time spent by processes blocked on the I/O requests that completed.
+    pub fn device_wait_ticks(&self) -> usize {
+        self.device_wait_ticks
+    }
+    /// Stop the simulation after `max_ticks` time units.
+    ///
+    /// Once the time reaches `max_ticks`, [`Scheduler::next`] returns
+    /// [`crate::SchedulingDecision::Done`], overriding all the other decisions,
+    /// even if there are processes left. The limit is checked every time a
+    /// decision is taken, so the last timeslice can end after it.
+    pub fn with_max_ticks(mut self, max_ticks: usize) -> Self {
+        self.max_ticks = Some(max_ticks);
+        self
+    }
+    /// Refill the CPU quotas of the processes every `quota_period` time units.
+    ///
+    /// The quotas set with [`Self::set_quota`] are only enforced
+    /// if a refill period is set.
+    pub fn with_quota_period(mut self, quota_period: NonZeroUsize) -> Self {
+        self.quota_period = Some(quota_period);
+        self
+    }
+    /// Limit the execution time of the process with the given pid to
+    /// `quota` time units in every quota period.
+    ///
+    /// A process that uses its whole quota is moved to the wait queue in the
+    /// [`ProcessState::Throttled`] state until the next period starts.
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn set_quota(&mut self, pid: Pid, quota: NonZeroUsize) -> bool {
+        // Search the process in all the queues
+        match self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            Some(proc) => {
+                proc.quota = Some(quota);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Give the process with the given pid its own timeslice, used instead
+    /// of the timeslice of the scheduler starting with its next dispatch.
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn set_timeslice(&mut self, pid: Pid, timeslice: NonZeroUsize) -> bool {
+        // Search the process in all the queues
+        match self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            Some(proc) => {
+                proc.timeslice = Some(timeslice);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Replace the hints of the process with the given pid, the children
+    /// that it forks afterwards receive the new hints.
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn set_meta(&mut self, pid: Pid, meta: ProcessMeta) -> bool {
+        // Search the process in all the queues
+        match self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            Some(proc) => {
+                proc.meta = meta;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Change the priority of the process with the given pid to `priority`,
+    /// as if it was created with it.
+    ///
+    /// A ready process moves to the end of the ready queue, behind the
+    /// processes that were ready before the change.
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn set_priority(&mut self, pid: Pid, priority: i8) -> bool {
+        let priority = self.policy.clamp_priority(priority);
+        if let Some(index) = self.ready.iter().position(|proc| proc.pid == pid) {
+            let proc = self.ready.remove(index);
+            self.ready.push(proc);
+        }
+        // Search the process in all the queues
+        match self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            Some(proc) => {
+                proc.priority = priority;
+                proc.default_priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Terminate the process with the given pid once its execution time
+    /// reaches `limit` time units, as if it has exited.
+    ///
+    /// The timeslices of the process are shortened so that it does not run
+    /// past its limit. The terminated processes are returned by
+    /// [`Self::timed_out`].
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn set_execution_limit(&mut self, pid: Pid, limit: NonZeroUsize) -> bool {
+        // Search the process in all the queues
+        match self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            Some(proc) => {
+                proc.execution_limit = Some(limit);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Returns the processes that were terminated because they reached their
+    /// execution limit, in the order in which they were terminated.
+    pub fn timed_out(&self) -> &[Pid] {
+        &self.timed_out
+    }
+    /// Give every forked child `quota_share` of the CPU quota of its parent,
+    /// and at least one time unit.
+    ///
+    /// The parent keeps its whole quota. By default, the children of a
+    /// process with a quota have no quota.
+    ///
+    /// ## Panics
+    ///
+    /// If the share is not between 0 and 1.
+    pub fn with_quota_share(mut self, quota_share: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&quota_share),
+            "the quota share is not between 0 and 1"
+        );
+        self.quota_share = quota_share;
+        self
+    }
+    /// Perturb the time that a process used before every system call by up
+    /// to `tick_jitter` time units, to simulate an imprecise clock.
+    ///
+    /// The error is a deterministic function of the pid of the process and of
+    /// the time, so simulations can be repeated. A process is always charged
+    /// at least the time unit of the system call. The default jitter of 0
+    /// keeps the accounting exact.
+    pub fn with_tick_jitter(mut self, tick_jitter: usize) -> Self {
+        self.tick_jitter = tick_jitter;
+        self
+    }
+    /// Keep every exited process as a zombie until it is reaped by its
+    /// parent with a [`Syscall::Join`] or with [`Self::reap`].
+    ///
+    /// Zombies keep their pids, so the pids are not reused until the
+    /// zombies are reaped.
+    pub fn with_zombies(mut self, keep_zombies: bool) -> Self {
+        self.keep_zombies = keep_zombies;
+        self
+    }
+    /// Give the pids of exited processes to new processes, the oldest
+    /// one first, before new pids are generated.
+    ///
+    /// By default, every process receives a new pid. Pid 1 is never reused.
+    pub fn with_pid_reuse(mut self, reuse_pids: bool) -> Self {
+        self.reuse_pids = reuse_pids;
+        self
+    }
+    /// Choose which of the waiters of an event a [`Syscall::SignalOne`] wakes up.
+    pub fn with_wake_policy(mut self, wake_policy: WakePolicy) -> Self {
+        self.wake_policy = wake_policy;
+        self
+    }
+    /// Give the init process (pid 1) the priority `init_priority`, instead
+    /// of the priority requested by the fork that creates it.
+    ///
+    /// By default, the init process keeps the priority of its fork.
+    pub fn with_init_priority(mut self, init_priority: i8) -> Self {
+        self.init_priority = Some(init_priority);
+        self
+    }
+    /// Preempt a process that forks, so that its child runs next,
+    /// followed by the parent.
+    ///
+    /// By default, the child is placed at the end of the ready queue and
+    /// the parent keeps running. A preempted parent receives a new timeslice
+    /// when it runs again. A process that disabled its preemption keeps running.
+    pub fn with_child_runs_first(mut self, child_runs_first: bool) -> Self {
+        self.child_runs_first = child_runs_first;
+        self
+    }
+    /// Let a process that uses its whole timeslice keep running when no other
+    /// process is ready, instead of moving it to the ready queue and selecting
+    /// it again.
+    ///
+    /// The process still receives a new timeslice, but its CPU burst continues,
+    /// so the burst histogram sees a single long burst instead of one burst for
+    /// every timeslice. As the process is not dispatched again, no involuntary
+    /// switch is counted and no dispatch latency is charged.
+    ///
+    /// The same as [`Self::with_alone_expiry`] with [`AloneExpiry::Continue`],
+    /// or [`AloneExpiry::Requeue`] if `no_reset_when_alone` is `false`.
+    pub fn with_no_reset_when_alone(self, no_reset_when_alone: bool) -> Self {
+        self.with_alone_expiry(if no_reset_when_alone {
+            AloneExpiry::Continue
+        } else {
+            AloneExpiry::Requeue
+        })
+    }
+    /// Choose what happens when the timeslice of the running process expires
+    /// and no other process is ready, [`AloneExpiry::Requeue`] by default.
+    pub fn with_alone_expiry(mut self, alone_expiry: AloneExpiry) -> Self {
+        self.alone_expiry = alone_expiry;
+        self
+    }
+    /// Let every process run for at least `min_run` time units of its CPU
+    /// burst before it is preempted because its timeslice expired.
+    ///
+    /// A process that expires too early keeps running with a new timeslice
+    /// that lasts until it reaches `min_run`, but not longer than the timeslice.
+    /// This is different from the minimum remaining timeslice, which decides
+    /// if the running process keeps the rest of its timeslice after a system
+    /// call. Blocking still ends the burst early.
+    pub fn with_min_run(mut self, min_run: usize) -> Self {
+        self.min_run = min_run;
+        self
+    }
+    /// Hand out every timeslice in intervals of `check_interval` time units,
+    /// and ask the policy at the end of every interval if a ready process
+    /// has to run before the running one, instead of only when the timeslice
+    /// expires.
+    ///
+    /// A preempted process runs again before the other ready processes,
+    /// otherwise it keeps running for the next interval, until it uses its
+    /// whole timeslice. See [`SelectionPolicy::preempts`].
+    pub fn with_preempt_check_interval(mut self, check_interval: NonZeroUsize) -> Self {
+        self.check_interval = Some(check_interval);
+        self
+    }
+    /// Round the amount of every sleep up to a multiple of `sleep_granularity`
+    /// time units, so that a `Sleep(3)` with a granularity of 4 lasts 4 time
+    /// units. A granularity of 0 or 1 keeps the amounts unchanged.
+    ///
+    /// Sleeps that end at different times can end at the same time after
+    /// they are rounded. The processes that wake up at the same time are
+    /// made ready in the order in which they went to sleep.
+    pub fn with_sleep_granularity(mut self, sleep_granularity: usize) -> Self {
+        self.sleep_granularity = sleep_granularity;
+        self
+    }
+    /// Keep track of the events that the processes wait for and of the
+    /// events that are signaled, so that [`Self::unsatisfied_events`] can
+    /// report the events that were never signaled.
+    pub fn with_event_check(mut self, event_check: bool) -> Self {
+        self.event_check = event_check;
+        self
+    }
+    /// Returns the events that processes waited for, but that were never
+    /// signaled, in ascending order.
+    ///
+    /// Usually called once the scheduler returns a [`crate::SchedulingDecision::Done`]
+    /// or a [`crate::SchedulingDecision::Deadlock`] decision. Returns an empty list
+    /// unless the check was enabled with [`Self::with_event_check`].
+    pub fn unsatisfied_events(&self) -> Vec<usize> {
+        self.waited_events
+            .difference(&self.signaled_events)
+            .copied()
+            .collect()
+    }
+    /// Returns every event that the processes from the wait queue are waiting
+    /// for, in ascending order, with the pids of the processes that wait for it.
+    ///
+    /// A process that waits with [`crate::Syscall::WaitAny`] is listed for
+    /// all its events.
+    pub fn awaited_events(&self) -> Vec<(usize, Vec<Pid>)> {
+        let mut awaited: BTreeMap<usize, Vec<Pid>> = BTreeMap::new();
+        for proc in &self.wait {
+            if !proc.events.is_empty() {
+                for event in proc.events.iter() {
+                    awaited.entry(event).or_default().push(proc.pid);
+                }
+            } else if let ProcessState::Waiting { event: Some(event) } = proc.state {
+                awaited.entry(event).or_default().push(proc.pid);
+            }
+        }
+        awaited.into_iter().collect()
+    }
+    /// Returns why the process with the given pid is blocked.
+    ///
+    /// Returns [`None`] if there is no such process or if it is not blocked.
+    pub fn block_reason(&self, pid: Pid) -> Option<BlockReason> {
+        if self.suspended.iter().any(|proc| proc.pid == pid) {
+            return Some(BlockReason::Suspended);
+        }
+        let proc = self.wait.iter().find(|proc| proc.pid == pid)?;
+        // The time until a sleeping process wakes up
+        let remaining = || {
+            self.sleepers
+                .iter()
+                .find(|Reverse((_, _, sleeper))| *sleeper == pid)
+                .map_or(0, |Reverse((wake_tick, _, _))| {
+                    wake_tick.saturating_sub(self.current_tick)
+                })
+        };
+        match proc.state {
+            ProcessState::Waiting { event: Some(_) } if !proc.events.is_empty() => {
+                Some(BlockReason::AnyEvent(proc.events.clone()))
+            }
+            ProcessState::Waiting { event: Some(event) } => Some(BlockReason::Event(event)),
+            ProcessState::Waiting { event: None } => match proc.joining {
+                Some(child) => Some(BlockReason::Join(child)),
+                None if proc.locking => Some(BlockReason::Lock),
+                None if proc.io_latency.is_some() => Some(BlockReason::Io {
+                    remaining: remaining(),
+                }),
+                None => Some(BlockReason::Sleeping {
+                    remaining: remaining(),
+                }),
+            },
+            ProcessState::Throttled { until } => Some(BlockReason::Throttled {
+                remaining: until.saturating_sub(self.current_tick),
+            }),
+            _ => None,
+        }
+    }
+    /// Stop the simulation with a [`crate::SchedulingDecision::Starvation`] decision if
+    /// a ready process waits more than `starvation_limit` time units to run.
+    ///
+    /// The time that the processor sleeps does not count, as no process is ready.
+    pub fn with_starvation_limit(mut self, starvation_limit: usize) -> Self {
+        self.starvation_limit = Some(starvation_limit);
+        self
+    }
+    /// Return a [`crate::SchedulingDecision::Stranded`] decision instead of
+    /// [`crate::SchedulingDecision::Done`] if a process is left in the queues.
+    ///
+    /// This also applies when the simulation reaches its maximum length.
+    pub fn with_strict_done(mut self, strict_done: bool) -> Self {
+        self.strict_done = strict_done;
+        self
+    }
+    /// Pass every decision taken by [`Scheduler::next`] through `filter`,
+    /// which can return it unchanged, veto it or replace it.
+    ///
+    /// If the filter returns a [`crate::SchedulingDecision::Run`] for another
+    /// process, the process chosen by the scheduler goes back to the front of
+    /// the ready queue and the returned process runs for the returned timeslice.
+    /// The time charged for the dispatch of the vetoed process is not given
+    /// back. If the returned process is neither the running one nor a ready
+    /// one, the process at the head of the ready queue runs instead, which is
+    /// the one chosen by the scheduler. If the filter
+    /// returns any other decision, the running process is moved back to the
+    /// ready queue, and a [`crate::SchedulingDecision::Sleep`] or a
+    /// [`crate::SchedulingDecision::Idle`] advances the time in the next call.
+    /// A process woken up to end a vetoed sleep stays ready.
+    ///
+    /// A clone of the scheduler receives its own copy of the filter, so the
+    /// state of the filter is not shared with the original.
+    pub fn set_decision_filter<F>(&mut self, filter: F)
+    where
+        F: FnMut(crate::SchedulingDecision) -> crate::SchedulingDecision + Clone + Send + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+    }
+    /// Compute [`Self::recent_utilization`] over the last
+    /// `utilization_window` time units, instead of the default 100.
+    pub fn with_utilization_window(mut self, utilization_window: NonZeroUsize) -> Self {
+        self.utilization_window = utilization_window;
+        self
+    }
+    /// Returns the fraction of the recent time units in which the processor
+    /// was busy, between 0 and 1.
+    ///
+    /// The processor is idle only while it sleeps, the time spent in context
+    /// switches and system calls counts as busy. Returns 0 before any time
+    /// passes.
+    pub fn recent_utilization(&self) -> f64 {
+        if self.recent_ticks.is_empty() {
+            return 0.0;
+        }
+        let busy = self.recent_ticks.iter().filter(|&&busy| busy).count();
+        busy as f64 / self.recent_ticks.len() as f64
+    }
+    /// Report the scaled times in `time_unit`.
+    ///
+    /// The scheduler still counts the time in ticks, only the methods that
+    /// return scaled times use the unit.
+    pub fn with_time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.time_unit = time_unit;
+        self
+    }
+    /// Returns the unit of the scaled times.
+    pub fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
+    /// Returns the timings of the process with the given pid, like
+    /// [`Process::timings`], with the total and the execution time
+    /// converted to the time unit.
+    ///
+    /// The number of system calls is not a time, so it is not converted.
+    /// Returns [`None`] if there is no such process.
+    pub fn timings_scaled(&self, pid: Pid) -> Option<(usize, usize, usize)> {
+        let (total, syscalls, execution) = self
+            .ready
+            .iter()
+            .chain(self.wait.iter())
+            .chain(self.suspended.iter())
+            .chain(self.running_process.iter())
+            .find(|proc| proc.pid == pid)?
+            .timings_at(self.current_tick);
+        Some((
+            self.time_unit.scale(total),
+            syscalls,
+            self.time_unit.scale(execution),
+        ))
+    }
+    /// Returns the timeslice of the scheduler, converted to the time unit.
+    pub fn timeslice_scaled(&self) -> usize {
+        self.time_unit.scale(self.timeslice.get())
+    }
+    /// Returns the time elapsed since the start of the simulation.
+    pub fn current_tick(&self) -> usize {
+        self.current_tick
+    }
+    /// Returns why the last call to [`Scheduler::next`] did not run a process,
+    /// or [`None`] if it did.
+    pub fn last_idle_reason(&self) -> Option<IdleReason> {
+        self.idle_reason
+    }
+    /// Returns the total time spent in context switches.
+    pub fn overhead_ticks(&self) -> usize {
+        self.overhead_ticks
+    }
+    /// Returns the statistics of the processes from [`Scheduler::list`],
+    /// grouped by their default priority, as the policy can change their
+    /// current priority while they run.
+    ///
+    /// The exited processes are not counted, their time can be found in
+    /// [`Self::zombies`] if they are kept.
+    pub fn priority_stats(&self) -> HashMap<i8, LevelStat> {
+        let mut stats: HashMap<i8, LevelStat> = HashMap::new();
+        for proc in self
+            .ready
+            .iter()
+            .chain(self.wait.iter())
+            .chain(self.suspended.iter())
+            .chain(self.running_process.iter())
+        {
+            let (total, syscalls, execution) = proc.timings_at(self.current_tick);
+            let stat = stats.entry(proc.default_priority).or_default();
+            stat.processes += 1;
+            stat.execution_ticks += execution;
+            // The rest of the time was spent in the queues
+            stat.wait_ticks += total - syscalls - execution;
+        }
+        stats
+    }
+    /// Returns the total time that the processor was idle, while it slept
+    /// because no process was ready.
+    pub fn idle_ticks(&self) -> usize {
+        self.idle_ticks
+    }
+    /// Returns the number of processes created since the start, including
+    /// the init process and the processes that have already exited.
+    /// Adopted processes are not counted.
+    pub fn total_spawned(&self) -> usize {
+        self.spawned
+    }
+    /// Returns the number of processes that exited since the start,
+    /// including the ones that were killed.
+    pub fn total_exited(&self) -> usize {
+        self.exited
+    }
+    /// Returns the number of processes that exited per time unit since the
+    /// start.
+    ///
+    /// A process counts as completed as soon as it exits or is killed, even
+    /// if it is kept as a zombie until its parent reaps it. Returns 0 before
+    /// any time passes.
+    pub fn throughput(&self) -> f64 {
+        if self.current_tick == 0 {
+            return 0.0;
+        }
+        self.exited as f64 / self.current_tick as f64
+    }
+    /// Returns the process that holds the kernel lock, if any.
+    pub fn lock_holder(&self) -> Option<Pid> {
+        self.lock_holder
+    }
+    /// Returns the tick at which the next sleeping process wakes up, without
+    /// advancing the time.
+    ///
+    /// The processes blocked on an I/O request or waiting for their CPU quota
+    /// sleep as well. Returns `None` if no process is sleeping.
+    pub fn next_wakeup(&self) -> Option<usize> {
+        self.sleepers
+            .peek()
+            .map(|Reverse((wake_tick, _, _))| *wake_tick)
+    }
+    /// Returns the total time spent dispatching processes.
+    pub fn dispatch_ticks(&self) -> usize {
+        self.dispatch_ticks
+    }
+    /// Returns the total time spent handling system calls.
+    pub fn syscall_ticks(&self) -> usize {
+        self.syscall_ticks
+    }
+    /// Returns the largest number of processes that were ready at the same
+    /// time, together with the time at which it was first reached.
+    ///
+    /// The running process is not counted.
+    pub fn max_ready_depth(&self) -> (usize, usize) {
+        self.max_ready_depth
+    }
+    pub fn generate_pid(&mut self) -> Pid {
+        self.spawned += 1;
+        // Reuse the pid of an exited process if possible
+        while let Some(pid) = self.free_pids.pop_front() {
+            if !self.is_pid_used(pid) {
+                return pid;
+            }
+        }
+        // Generate a new PID, skipping the pids that are still in use
+        loop {
+            let new_pid = Pid::new(self.pid_counter);
+            // Wrap around on overflow, pid 1 is reserved for the init process
+            self.pid_counter = self.pid_counter.checked_add(1).unwrap_or(2);
+            if !self.is_pid_used(new_pid) {
+                return new_pid;
+            }
+        }
+    }
+    fn free_pid(&mut self, pid: Pid) {
+        if self.reuse_pids {
+            self.free_pids.push_back(pid);
+        }
+    }
+    fn is_pid_used(&self, pid: Pid) -> bool {
+        // Check if any process from the scheduler has this pid
+        self.ready
+            .iter()
+            .chain(self.wait.iter())
+            .chain(self.suspended.iter())
+            .chain(self.running_process.iter())
+            .any(|proc| proc.pid == pid)
+            // A zombie keeps its pid until it is reaped
+            || self.zombies.iter().any(|zombie| zombie.pid == pid)
+    }
+    /// Place a new process directly in the given state, without a fork.
+    ///
+    /// This is used to set up a specific configuration of the queues.
+    /// A sleeping process also needs a sleep amount, so it has to be
+    /// injected using [`Self::inject_sleep`].
+    ///
+    /// ## Panics
+    ///
+    /// If the state is [`ProcessState::Running`] and another process is
+    /// already running, or if the state is a sleep or [`ProcessState::Exited`].
+    /// A suspended process is placed in the suspended list and a throttled
+    /// process becomes ready at the time given by its state.
+    pub fn inject(&mut self, state: ProcessState, priority: i8) -> Pid {
+        let pid = self.generate_pid();
+        let priority = self.policy.clamp_priority(priority);
+        let mut proc = ProcessInfo::new(pid, priority, self.current_tick);
+        proc.state = state;
+        match state {
+            ProcessState::Ready => self.push_ready(proc),
+            ProcessState::Running => {
+                assert!(
+                    self.running_process.is_none(),
+                    "another process is already running"
+                );
+                self.start_slice(self.timeslice.get());
+                proc.first_run = Some(self.current_tick);
+                proc.queued_since = None;
+                self.running_process = Some(proc);
+            }
+            ProcessState::Waiting { event: Some(_) } => self.push_wait(proc),
+            ProcessState::Waiting { event: None } => {
+                panic!("a sleeping process needs a sleep amount, use inject_sleep")
+            }
+            ProcessState::Exited => panic!("an exited process can not be injected"),
+            ProcessState::Suspended => self.suspended.push(proc),
+            ProcessState::Throttled { until } => {
+                self.push_wait(proc);
+                self.push_sleeper(pid, until.saturating_sub(self.current_tick));
+            }
+        }
+        pid
+    }
+    /// Place a new sleeping process directly in the wait queue, it will
+    /// wake up after `amount` time units.
+    pub fn inject_sleep(&mut self, amount: usize, priority: i8) -> Pid {
+        let pid = self.generate_pid();
+        let priority = self.policy.clamp_priority(priority);
+        let mut proc = ProcessInfo::new(pid, priority, self.current_tick);
+        proc.state = ProcessState::Waiting { event: None };
+        self.push_wait(proc);
+        self.push_sleeper(pid, self.round_sleep(amount));
+        pid
+    }
+    /// Returns how many CPU bursts ended with a block, an exit or an expiry,
+    /// for each range of burst lengths. The ranges grow as powers of two.
+    pub fn burst_histogram(&self) -> Vec<(Range<usize>, usize)> {
+        self.bursts
+            .iter()
+            .enumerate()
+            .map(|(bucket, &count)| {
+                let range = match bucket {
+                    0 => 0..1,
+                    _ => 1 << (bucket - 1)..1 << bucket,
+                };
+                (range, count)
+            })
+            .collect()
+    }
+    fn account_syscall(&mut self, proc: &mut ProcessInfo, elapsed: usize) {
+        self.policy.system_call(proc);
+        // The result of the previous blocking system call is not needed anymore
+        proc.wake_result = None;
+        // Update the timings of the process, the system call takes the last time unit
+        proc.timings.0 += elapsed;
+        proc.burst += elapsed;
+        proc.timings.1 += 1;
+        proc.timings.2 += elapsed - 1;
+    }
+    fn record_burst(&mut self, proc: &mut ProcessInfo) {
+        // The bucket of a burst is given by the number of bits of its length
+        let bucket = (usize::BITS - proc.burst.leading_zeros()) as usize;
+        if self.bursts.len() <= bucket {
+            self.bursts.resize(bucket + 1, 0);
+        }
+        self.bursts[bucket] += 1;
+        self.policy.burst_completed(proc, proc.burst);
+        proc.burst = 0;
+    }
+    fn process_timeslice(&self, proc: &ProcessInfo) -> NonZeroUsize {
+        // A process with its own timeslice does not use the one of the scheduler
+        proc.timeslice.unwrap_or(self.timeslice)
+    }
+    fn dispatch_timeslice(&self) -> NonZeroUsize {
+        // Split the target latency between the runnable processes
+        // (the dispatched process is not in the ready queue anymore)
+        let timeslice = match self.target_latency {
+            Some(target_latency) => {
+                let timeslice = target_latency.get() / (self.ready.len() + 1);
+                NonZeroUsize::new(timeslice)
+                    .unwrap_or(self.min_slice)
+                    .max(self.min_slice)
+            }
+            None => self.timeslice,
+        };
+        let Some(proc) = &self.running_process else {
+            return timeslice;
+        };
+        // The own timeslice of the process replaces the one of the scheduler
+        let timeslice = proc.timeslice.unwrap_or(timeslice);
+        // The running process can't run past its execution limit
+        let timeslice = match proc.execution_limit {
+            Some(limit) => timeslice.min(
+                NonZeroUsize::new(limit.get().saturating_sub(proc.timings.2))
+                    .unwrap_or(NonZeroUsize::MIN),
+            ),
+            None => timeslice,
+        };
+        // The running process can't exceed its quota or use time from the next period
+        // (a process without quota left is throttled before it gets a new quanta)
+        let (Some(quota), Some(period)) = (proc.quota, self.quota_period) else {
+            return timeslice;
+        };
+        let left = quota
+            .get()
+            .saturating_sub(proc.timings.2 - proc.period_start);
+        let until_refill = period.get() - self.current_tick % period.get();
+        timeslice
+            .min(NonZeroUsize::new(left).unwrap_or(NonZeroUsize::MIN))
+            .min(NonZeroUsize::new(until_refill).unwrap())
+    }
+    fn elapsed(&self, remaining: usize) -> usize {
+        // A system call that is not made by a running process does not use any time
+        if self.running_process.is_none() {
+            return 0;
+        }
+        let elapsed = self.remaining_running_time - remaining;
+        let Some(proc) = self
+            .running_process
+            .as_ref()
+            .filter(|_| self.tick_jitter > 0)
+        else {
+            return elapsed;
+        };
+        // Derive an error between -tick_jitter and tick_jitter from the pid and the time
+        let mut hasher = DefaultHasher::new();
+        (proc.pid, self.current_tick).hash(&mut hasher);
+        let error = (hasher.finish() % (2 * self.tick_jitter as u64 + 1)) as isize
+            - self.tick_jitter as isize;
+        elapsed.saturating_add_signed(error).max(elapsed.min(1))
+    }
+    fn throttle_amount(&self, proc: &ProcessInfo) -> Option<usize> {
+        // Check if the process has used its quota in the current period
+        let period = self.quota_period?;
+        let quota = proc.quota?;
+        if proc.timings.2 - proc.period_start < quota.get() {
+            return None;
+        }
+        // It has to wait until the next period starts
+        Some(period.get() - self.current_tick % period.get())
+    }
+    fn throttle(&mut self, mut proc: ProcessInfo, amount: usize) {
+        // The process waits in the wait queue until the next quota period
+        proc.state = ProcessState::Throttled {
+            until: self.current_tick + amount,
+        };
+        proc.io_latency = None;
+        self.push_sleeper(proc.pid, amount);
+        self.push_wait(proc);
+    }
+    fn throttle_exhausted(&mut self) {
+        // A process without quota left can't get a new quanta before the next period
+        if self.running_process.as_ref().is_some_and(|proc| {
+            self.remaining_running_time == 0
+                && self.slice_left == 0
+                && self.throttle_amount(proc).is_some()
+        }) {
+            let mut proc = self.running_process.take().unwrap();
+            let amount = self.throttle_amount(&proc).unwrap();
+            proc.involuntary_switches += 1;
+            self.record_burst(&mut proc);
+            self.throttle(proc, amount);
+        }
+        let (exhausted, ready): (Vec<_>, Vec<_>) = std::mem::take(&mut self.ready)
+            .into_iter()
+            .partition(|proc| self.throttle_amount(proc).is_some());
+        self.ready = ready;
+        for proc in exhausted {
+            let amount = self.throttle_amount(&proc).unwrap();
+            self.throttle(proc, amount);
+        }
+    }
+    fn child_quota(&self, parent: &ProcessInfo) -> Option<NonZeroUsize> {
+        // The child receives a share of the quota of its parent, the priority comes from the fork
+        let quota = parent.quota?;
+        if self.quota_share <= 0.0 {
+            return None;
+        }
+        let share = (quota.get() as f64 * self.quota_share) as usize;
+        Some(NonZeroUsize::new(share).unwrap_or(NonZeroUsize::MIN))
+    }
+    fn refill_quotas(&mut self) {
+        // Start a new quota period for all the processes when the time reaches it
+        let Some(period) = self.quota_period else {
+            return;
+        };
+        if self.current_tick / period.get() == self.quota_window {
+            return;
+        }
+        self.quota_window = self.current_tick / period.get();
+        for proc in self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+        {
+            proc.period_start = proc.timings.2;
+        }
+    }
+    fn context_switch(&mut self, proc: &mut ProcessInfo) {
+        proc.dequeue(self.current_tick);
+        // Charge the switch cost if a different process was scheduled before
+        if self.last_pid != Some(proc.pid) {
+            self.last_pid = Some(proc.pid);
+            if self.switch_cost > 0 {
+                self.increase_timings(self.switch_cost);
+                proc.timings.0 += self.switch_cost;
+                self.overhead_ticks += self.switch_cost;
+            }
+        }
+        // Charge the time needed to load the process
+        if self.dispatch_latency > 0 {
+            self.increase_timings(self.dispatch_latency);
+            proc.timings.0 += self.dispatch_latency;
+            self.dispatch_ticks += self.dispatch_latency;
+        }
+    }
+    fn charge_syscall(&mut self) {
+        // The fork of the first process is not made by any process
+        if self.syscall_cost > 0 && self.running_process.is_some() {
+            self.increase_timings(self.syscall_cost);
+            self.running_process.as_mut().unwrap().timings.0 += self.syscall_cost;
+            self.syscall_ticks += self.syscall_cost;
+        }
+    }
+    /// Preempt the running process at an arbitrary point of its timeslice,
+    /// like a timer or device interrupt, with `remaining` time units left.
+    ///
+    /// The process is placed at the end of the ready queue, the same as when
+    /// its timeslice expires, and the switch is counted as an interrupt. The
+    /// interrupt handler takes the last time unit used by the process, like a
+    /// system call. A process that disabled its preemption keeps running with
+    /// the rest of its timeslice.
+    ///
+    /// Returns [`SyscallResult::NoRunningProcess`] if no process is running,
+    /// or an error if `remaining` is larger than the time left in the current
+    /// interval of the running process, in which case nothing changes.
+    pub fn interrupt(&mut self, remaining: usize) -> Result<SyscallResult, String> {
+        if self.running_process.is_none() {
+            return Ok(SyscallResult::NoRunningProcess);
+        }
+        if remaining > self.remaining_running_time {
+            return Err(format!(
+                "the interrupt leaves {} time units, but the process only had {}",
+                remaining, self.remaining_running_time
+            ));
+        }
+        let mut running_process = self.running_process.take().unwrap();
+        // Update the timings with the time used until the interrupt
+        let elapsed = self.remaining_running_time - remaining;
+        self.increase_timings(elapsed);
+        running_process.timings.0 += elapsed;
+        running_process.burst += elapsed;
+        if elapsed > 0 {
+            running_process.timings.1 += 1;
+            running_process.timings.2 += elapsed - 1;
+        }
+        if running_process.preempt_disabled {
+            // The rest of the timeslice is handed out in intervals again
+            let left = remaining + self.slice_left;
+            self.running_process = Some(running_process);
+            self.start_slice(left);
+            return Ok(SyscallResult::Success);
+        }
+        running_process.state = ProcessState::Ready;
+        running_process.involuntary_switches += 1;
+        self.interrupts += 1;
+        self.record_burst(&mut running_process);
+        self.push_ready(running_process);
+        // Reset the running process
+        self.remaining_running_time = self.timeslice.into();
+        self.slice_left = 0;
+        Ok(SyscallResult::Success)
+    }
+    /// Returns the number of times the running process was preempted by
+    /// [`Self::interrupt`].
+    pub fn interrupts(&self) -> usize {
+        self.interrupts
+    }
+    /// Terminate the process with the given pid, as if it has exited.
+    ///
+    /// Returns `false` if there is no such process.
+    pub fn kill(&mut self, pid: Pid) -> bool {
+        match self.extract(pid) {
+            Some(proc) => {
+                self.release(proc);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Stop scheduling the process with the given pid until it is resumed
+    /// with [`Self::resume`].
+    ///
+    /// Only a ready or running process can be suspended, a running process
+    /// loses the rest of its timeslice. The simulation is not done while a
+    /// process is suspended, the processor sleeps until it is resumed. The
+    /// waiting processes are not deadlocked either, as a suspended process
+    /// can still wake them up.
+    ///
+    /// Returns `false` if there is no such ready or running process.
+    pub fn suspend(&mut self, pid: Pid) -> bool {
+        let runnable = self
+            .ready
+            .iter()
+            .chain(self.running_process.iter())
+            .any(|proc| proc.pid == pid);
+        if !runnable {
+            return false;
+        }
+        let mut proc = self.extract(pid).unwrap();
+        proc.state = ProcessState::Suspended;
+        proc.enqueue(self.current_tick);
+        self.suspended.push(proc);
+        true
+    }
+    /// Place a suspended process back at the end of the ready queue.
+    ///
+    /// Returns `false` if there is no such suspended process.
+    pub fn resume(&mut self, pid: Pid) -> bool {
+        match self.suspended.iter().position(|proc| proc.pid == pid) {
+            Some(index) => {
+                let mut proc = self.suspended.remove(index);
+                proc.state = ProcessState::Ready;
+                self.push_ready(proc);
+                true
+            }
+            None => false,
+        }
+    }
+    fn release(&mut self, mut proc: ProcessInfo) {
+        let pid = proc.pid;
+        self.exited += 1;
+        self.end_donations(&mut proc, true);
+        self.release_lock(pid);
+        proc.exited = Some(self.current_tick);
+        if pid == 1 {
+            // Verify if process with pid 1 has exited
+            self.init = true;
+        } else {
+            if !self.keep_zombies {
+                // Its pid can be given to a new process
+                self.free_pid(pid);
+            }
+            // Its children are adopted by the init process
+            for child in self
+                .ready
+                .iter_mut()
+                .chain(self.wait.iter_mut())
+                .chain(self.suspended.iter_mut())
+                .chain(self.zombies.iter_mut())
+            {
+                if child.parent == Some(pid) {
+                    child.parent = Some(Pid::new(1));
+                }
+            }
+        }
+        if let Some(parent) = proc.parent {
+            // Wake up the parent if it waits for this process
+            if let Some(index) = self
+                .wait
+                .iter()
+                .position(|waiting| waiting.pid == parent && waiting.joining == Some(pid))
+            {
+                let mut waiting = self.remove_wait(index);
+                waiting.joining = None;
+                waiting.state = ProcessState::Ready;
+                waiting.wake_result = Some(SyscallResult::Joined(proc.timings));
+                log::trace!(
+                    "tick {}: process {} joined process {}",
+                    self.current_tick,
+                    parent,
+                    pid
+                );
+                self.push_ready(waiting);
+                // The child is reaped by its parent right away
+                if self.keep_zombies {
+                    self.free_pid(pid);
+                }
+                return;
+            }
+        }
+        // Keep the process until it is reaped, the parent can join it
+        if self.keep_zombies {
+            proc.state = ProcessState::Exited;
+            self.zombies.push(proc);
+        }
+    }
+    /// Adds the time that the queued processes spent in the queues until now
+    /// to their total time, so that it is reported by [`Process::timings`].
+    fn settle_queues(&mut self) {
+        let tick = self.current_tick;
+        for proc in self
+            .ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.suspended.iter_mut())
+        {
+            proc.settle(tick);
+        }
+    }
+    /// Returns the ready, waiting and running processes, like
+    /// [`Scheduler::list`], without borrowing the scheduler mutably.
+    fn processes(&self) -> Vec<&dyn Process> {
+        // List all processes from my Scheduler
+        let mut list: Vec<&dyn Process> = Vec::new();
+        for i in &self.ready {
+            // Add the processes from the ready queue
+            list.push(i)
+        }
+        for i in self.wait.iter().chain(self.suspended.iter()) {
+            list.push(i)
+        }
+        if let Some(x) = &self.running_process {
+            list.push(x);
+        }
+        list
+    }
+    /// Returns the exited processes that were not reaped yet, in the order
+    /// in which they exited.
+    pub fn zombies(&self) -> Vec<&dyn Process> {
+        self.zombies
+            .iter()
+            .map(|proc| proc as &dyn Process)
+            .collect()
+    }
+    /// Returns a CSV table with a row for every process, sorted by pid.
+    ///
+    /// The columns are always, in this order: `pid`, `priority`, `total`,
+    /// `syscalls`, `execution`, `response`, `turnaround` and `slowdown`. The
+    /// `response` time lasts from the creation of the process until it is
+    /// dispatched for the first time, and the `turnaround` time until it exits.
+    /// The `slowdown` is described by [`Self::slowdown`]. They are empty while
+    /// the process has not run or exited.
+    ///
+    /// Exited processes are only included while they are kept as zombies.
+    pub fn export_csv(&self) -> String {
+        let mut processes: Vec<&ProcessInfo> = self
+            .ready
+            .iter()
+            .chain(self.wait.iter())
+            .chain(self.suspended.iter())
+            .chain(self.running_process.iter())
+            .chain(self.zombies.iter())
+            .collect();
+        processes.sort_by_key(|proc| proc.pid);
+        let mut csv =
+            String::from("pid,priority,total,syscalls,execution,response,turnaround,slowdown\n");
+        for proc in processes {
+            let since_created = |time: Option<usize>| {
+                time.map_or(String::new(), |time| (time - proc.created).to_string())
+            };
+            let (total, syscalls, execution) = proc.timings_at(self.current_tick);
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                proc.pid,
+                proc.priority,
+                total,
+                syscalls,
+                execution,
+                since_created(proc.first_run),
+                since_created(proc.exited),
+                proc.slowdown()
+                    .map_or(String::new(), |slowdown| format!("{slowdown:.2}"))
+            ));
+        }
+        csv
+    }
+    /// Returns the slowdown of the exited process with the given pid, its
+    /// turnaround time divided by its execution time.
+    ///
+    /// A slowdown of 1 means that the process never waited. Returns [`None`]
+    /// if there is no such zombie or if it never executed.
+    pub fn slowdown(&self, pid: Pid) -> Option<f64> {
+        self.zombies
+            .iter()
+            .find(|zombie| zombie.pid == pid)?
+            .slowdown()
+    }
+    /// Remove the zombie with the given pid and return it.
+    ///
+    /// Returns [`None`] if there is no such zombie.
+    pub fn reap(&mut self, pid: Pid) -> Option<ProcessInfo> {
+        let index = self.zombies.iter().position(|zombie| zombie.pid == pid)?;
+        Some(self.reap_at(index))
+    }
+    fn reap_at(&mut self, index: usize) -> ProcessInfo {
+        let zombie = self.zombies.remove(index);
+        if zombie.pid != 1 {
+            // Its pid can be given to a new process
+            self.free_pid(zombie.pid);
+        }
+        zombie
+    }
+    /// Remove the process with the given pid from the scheduler, together
+    /// with its state and timings, so that it can be moved to another
+    /// scheduler with [`Self::adopt`].
+    ///
+    /// A sleeping process loses the rest of its sleep.
+    pub fn extract(&mut self, pid: Pid) -> Option<ProcessInfo> {
+        let mut proc = if self
+            .running_process
+            .as_ref()
+            .is_some_and(|proc| proc.pid == pid)
+        {
+            // Reset the running process
+            self.remaining_running_time = self.timeslice.into();
+            self.running_process.take()?
+        } else if let Some(index) = self.ready.iter().position(|proc| proc.pid == pid) {
+            self.ready.remove(index)
+        } else if let Some(index) = self.wait.iter().position(|proc| proc.pid == pid) {
+            self.remove_wait(index)
+        } else {
+            let index = self.suspended.iter().position(|proc| proc.pid == pid)?;
+            self.suspended.remove(index)
+        };
+        proc.dequeue(self.current_tick);
+        // The lock and the donations can't be kept by a process that left the scheduler
+        self.end_donations(&mut proc, true);
+        self.release_lock(pid);
+        Some(proc)
+    }
+    /// Place a process extracted from another scheduler in the ready queue.
+    ///
+    /// If its pid is already used by a process of this scheduler, the
+    /// process receives a new pid. Returns the pid of the adopted process.
+    pub fn adopt(&mut self, mut proc: ProcessInfo) -> Pid {
+        // Renumber the process if its pid collides with an existing one
+        if self.is_pid_used(proc.pid) {
+            proc.pid = self.generate_pid();
+            // It was not created by this scheduler
+            self.spawned -= 1;
+        }
+        // Its parent belongs to the other scheduler
+        proc.parent = None;
+        proc.joining = None;
+        proc.locking = false;
+        proc.events = EventSet::default();
+        proc.wake_result = None;
+        self.zombies.retain(|zombie| zombie.pid != proc.pid);
+        proc.state = ProcessState::Ready;
+        proc.io_latency = None;
+        let pid = proc.pid;
+        self.push_ready(proc);
+        pid
+    }
+    /// Remove the running process from the scheduler together with the
+    /// rest of its timeslice, so that it can be resumed later with
+    /// [`Self::restore_running`], in this or another scheduler.
+    ///
+    /// Returns [`None`] if no process is running.
+    pub fn checkpoint_running(&mut self) -> Option<(ProcessInfo, usize)> {
+        let proc = self.running_process.take()?;
+        let remaining = self.remaining_running_time + self.slice_left;
+        // Reset the running process
+        self.remaining_running_time = self.timeslice.into();
+        self.slice_left = 0;
+        Some((proc, remaining))
+    }
+    /// Make a process saved by [`Self::checkpoint_running`] the running
+    /// process, with `remaining` time units left from its timeslice.
+    ///
+    /// The process keeps its pid, its parent and its timings.
+    ///
+    /// ## Panics
+    ///
+    /// If another process is running, if the pid of the process is already
+    /// used or if `remaining` is 0.
+    pub fn restore_running(&mut self, mut proc: ProcessInfo, remaining: usize) {
+        assert!(
+            self.running_process.is_none(),
+            "another process is already running"
+        );
+        assert!(
+            !self.is_pid_used(proc.pid),
+            "the pid {} is already used",
+            proc.pid
+        );
+        assert!(remaining > 0, "the remaining time is 0");
+        self.zombies.retain(|zombie| zombie.pid != proc.pid);
+        proc.state = ProcessState::Running;
+        self.running_process = Some(proc);
+        self.start_slice(remaining);
+    }
+    fn fork_pending(&mut self, parent: &mut ProcessInfo) {
+        // Create the children deferred by the process when it gets a new quanta, in order
+        let quota = self.child_quota(parent);
+        for priority in parent.pending_forks.drain(..) {
+            let pid = self.generate_pid();
+            let priority = self.policy.clamp_priority(priority);
+            let mut child = ProcessInfo::new(pid, priority, self.current_tick);
+            child.parent = Some(parent.pid);
+            child.meta = parent.meta.clone();
+            child.quota = quota;
+            self.push_ready(child);
+        }
+    }
+    fn end_donations(&mut self, proc: &mut ProcessInfo, exited: bool) {
+        // The donations to a process end when it blocks, the ones it made end when it exits
+        let pid = proc.pid;
+        let (ended, kept): (Vec<_>, Vec<_>) = self
+            .donations
+            .iter()
+            .partition(|&&(donor, recipient, _)| recipient == pid || (exited && donor == pid));
+        self.donations = kept;
+        for (donor, recipient, amount) in ended {
+            let amount = i16::from(amount);
+            if recipient == pid {
+                proc.donation -= amount;
+                self.change_donation(donor, amount);
+            } else {
+                proc.donation += amount;
+                self.change_donation(recipient, -amount);
+            }
+        }
+    }
+    fn change_donation(&mut self, pid: Pid, change: i16) {
+        // A ready process moves to the end of the ready queue, as its priority changed
+        if let Some(index) = self.ready.iter().position(|proc| proc.pid == pid) {
+            let mut proc = self.ready.remove(index);
+            proc.donation += change;
+            self.ready.push(proc);
+        } else if let Some(proc) = self
+            .wait
+            .iter_mut()
+            .chain(self.suspended.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)
+        {
+            proc.donation += change;
+        }
+    }
+    fn push_ready(&mut self, mut proc: ProcessInfo) {
+        proc.enqueue(self.current_tick);
+        proc.ready_at = self.busy_ticks();
+        self.ready.push(proc);
+        self.record_ready_depth();
+    }
+    fn start_slice(&mut self, timeslice: usize) {
+        // The timeslice is handed out in intervals, to check for preemption between them
+        let interval = self
+            .check_interval
+            .map_or(timeslice, |interval| interval.get().min(timeslice));
+        self.remaining_running_time = interval;
+        self.slice_left = timeslice - interval;
+    }
+    fn next_interval(&mut self) {
+        // Continue the timeslice of the running process with its next interval
+        let left = self.slice_left;
+        self.start_slice(left);
+    }
+    fn push_ready_front(&mut self, mut proc: ProcessInfo) {
+        proc.enqueue(self.current_tick);
+        proc.ready_at = self.busy_ticks();
+        self.ready.insert(0, proc);
+        self.record_ready_depth();
+    }
+    fn push_wait(&mut self, mut proc: ProcessInfo) {
+        proc.enqueue(self.current_tick);
+        self.wait.push(proc);
+    }
+    fn busy_ticks(&self) -> usize {
+        // The time in which the processor did not sleep
+        self.current_tick - self.idle_ticks
+    }
+    fn record_ready_depth(&mut self) {
+        // Keep the first time the ready queue reached its largest size
+        let depth = self.ready.len();
+        if depth > self.max_ready_depth.0 {
+            self.max_ready_depth = (depth, self.current_tick);
+        }
+    }
+    fn fork_priority(&self, pid: Pid, priority: i8) -> i8 {
+        // The init process can have a fixed priority
+        match self.init_priority {
+            Some(init_priority) if pid == 1 => init_priority,
+            _ => priority,
+        }
+    }
+    fn done(&self) -> crate::SchedulingDecision {
+        // A strict scheduler is only done once all the processes have exited
+        if self.strict_done {
+            if let Some(proc) = self
+                .running_process
+                .iter()
+                .chain(self.ready.iter())
+                .chain(self.wait.iter())
+                .chain(self.suspended.iter())
+                .next()
+            {
+                return crate::SchedulingDecision::Stranded(proc.pid);
+            }
+        }
+        crate::SchedulingDecision::Done
+    }
+    fn starving_process(&self) -> Option<Pid> {
+        // The ready process that has been waiting for the longest time, if over the limit
+        let limit = self.starvation_limit?;
+        let busy = self.busy_ticks();
+        self.ready
+            .iter()
+            .map(|proc| (busy - proc.ready_at, proc.pid))
+            .filter(|&(starving, _)| starving > limit)
+            .max_by_key(|&(starving, pid)| (starving, Reverse(pid)))
+            .map(|(_, pid)| pid)
+    }
+    fn pick_waiter(&self, event: usize) -> Option<usize> {
+        // The wait queue keeps the processes in the order in which they started to wait
+        let mut waiters = self
+            .wait
+            .iter()
+            .enumerate()
+            .filter(|(_, proc)| proc.waits_for(event));
+        let waiter = match self.wake_policy {
+            WakePolicy::LongestWaiting => waiters.next(),
+            WakePolicy::HighestPriority => {
+                waiters.min_by_key(|(_, proc)| Reverse(proc.effective_priority()))
+            }
+        };
+        waiter.map(|(index, _)| index)
+    }
+    fn release_lock(&mut self, pid: Pid) {
+        if self.lock_holder != Some(pid) {
+            return;
+        }
+        // The wait queue keeps the processes in the order in which they started to wait
+        self.lock_holder = None;
+        if let Some(index) = self.wait.iter().position(|proc| proc.locking) {
+            let mut waiting = self.remove_wait(index);
+            waiting.locking = false;
+            waiting.state = ProcessState::Ready;
+            self.lock_holder = Some(waiting.pid);
+            log::trace!(
+                "tick {}: process {} acquired the lock released by process {}",
+                self.current_tick,
+                waiting.pid,
+                pid
+            );
+            self.push_ready(waiting);
+        }
+    }
+    fn remove_wait(&mut self, index: usize) -> ProcessInfo {
+        // Remove a process from the wait queue, together with its sleep
+        let proc = self.wait.remove(index);
+        self.sleepers
+            .retain(|&Reverse((_, _, pid))| pid != proc.pid);
+        proc
+    }
+    fn round_sleep(&self, amount: usize) -> usize {
+        // Round the sleep amount up to the granularity of the sleeps
+        amount.next_multiple_of(self.sleep_granularity.max(1))
+    }
+    fn push_sleeper(&mut self, pid: Pid, amount: usize) {
+        // The processes that wake up at the same time keep the order in which they went to sleep
+        self.sleepers.push(Reverse((
+            self.current_tick + amount,
+            self.sleep_counter,
+            pid,
+        )));
+        self.sleep_counter += 1;
+    }
+    pub fn increase_timings(&mut self, amount: usize) {
+        // Advance the clock
+        self.current_tick += amount;
+        // Only the last ticks of a long interval stay in the window
+        let window = self.utilization_window.get();
+        for _ in 0..amount.min(window) {
+            if self.recent_ticks.len() >= window {
+                self.recent_ticks.pop_front();
+            }
+            self.recent_ticks.push_back(self.sleep == 0);
+        }
+        // The processes from the queues count their time from the clock when they leave them
+        // Take the processes that have to wake up from the heap
+        let mut awakened = Vec::new();
+        while let Some(&Reverse((wake_tick, order, pid))) = self.sleepers.peek() {
+            if wake_tick > self.current_tick {
+                break;
+            }
+            self.sleepers.pop();
+            awakened.push((order, pid));
+        }
+        // Move them from the wait queue to the ready queue, in the order in which they went to sleep
+        awakened.sort_unstable();
+        for (_, pid) in awakened {
+            let index = self.wait.iter().position(|proc| proc.pid == pid).unwrap();
+            let mut proc = self.wait.remove(index);
+            // Account the time waited for a device
+            if let Some(latency) = proc.io_latency.take() {
+                self.device_wait_ticks += latency;
+            }
+            // A throttled process did not sleep by itself
+            if !matches!(proc.state, ProcessState::Throttled { .. }) {
+                self.policy.woken(&mut proc);
+            }
+            proc.state = ProcessState::Ready;
+            log::trace!("tick {}: process {} woke up", self.current_tick, pid);
+            self.push_ready(proc);
+        }
+    }
+    /// Take the next decision, before it is passed to the decision filter.
+    fn decide(&mut self) -> crate::SchedulingDecision {
+        // Increase all timings after a sleep (if 0, it will increase with 0)
+        // (the processor was idle during a sleep, no process waited to run)
+        self.idle_ticks += self.sleep;
+        self.increase_timings(self.sleep);
+        self.sleep = 0;
+        self.idle_reason = None;
+
+        // The simulation is done once it reaches its maximum length
+        if self
+            .max_ticks
+            .is_some_and(|max_ticks| self.current_tick >= max_ticks)
+        {
+            self.idle_reason = Some(IdleReason::TickLimit);
+            return self.done();
+        }
+        self.refill_quotas();
+        self.throttle_exhausted();
+
+        // A process that waits too long to run is a fairness bug
+        if let Some(pid) = self.starving_process() {
+            return crate::SchedulingDecision::Starvation(pid);
+        }
+
+        match self.running_process.take() {
+            Some(mut running_process) => {
+                // If there is a running process, check if it can be rescheduled
+                // (the minimum can't exceed the timeslice of the process)
+                // (with the rest of its timeslice after the current interval)
+                let minimum = self
+                    .minimum_remaining_timeslice
+                    .min(self.process_timeslice(&running_process).get());
+                if self.remaining_running_time + self.slice_left < minimum
+                    && !running_process.preempt_disabled
+                {
+                    // Can't reschedule, mark it as ready and push it to the ready queue
+                    running_process.state = ProcessState::Ready;
+                    running_process.enqueue(self.current_tick);
+                    running_process.ready_at = self.busy_ticks();
+                    self.ready.push(running_process);
+                    // Get the process picked by the policy and mark it as running
+                    let index = self.policy.pick(&self.ready);
+                    let proc = self.ready.remove(index);
+                    self.record_ready_depth();
+                    self.dispatch(proc);
+                    self.start_slice(self.dispatch_timeslice().get());
+                    // Return its pid and timeslice
+                    crate::SchedulingDecision::Run {
+                        pid: self.running_process.as_ref().unwrap().pid(),
+                        timeslice: NonZeroUsize::new(self.remaining_running_time).unwrap(),
+                    }
+                } else {
+                    // A process that can't be preempted gets a new quanta when it used its own
+                    let renewed = self.remaining_running_time == 0 && self.slice_left == 0;
+                    if renewed {
+                        self.fork_pending(&mut running_process);
+                    }
+                    // Regain ownership
+                    self.running_process = Some(running_process);
+                    if self.remaining_running_time == 0 && self.slice_left > 0 {
+                        self.next_interval();
+                    } else if renewed {
+                        self.start_slice(self.dispatch_timeslice().get());
+                    }
+                    // Reschedule the running process again
+                    crate::SchedulingDecision::Run {
+                        pid: self.running_process.as_ref().unwrap().pid(),
+                        timeslice: NonZeroUsize::new(self.remaining_running_time).unwrap(),
+                    }
+                }
+            }
+            None => {
+                // There is no running process
+                if !self.ready.is_empty() {
+                    // Check for panic (if the process with pid 1 has exited)
+                    if self.init {
+                        self.init = false;
+                        self.idle_reason = Some(IdleReason::InitExited);
+                        return crate::SchedulingDecision::Panic;
+                    }
+                    // Return the process picked by the policy
+                    let index = self.policy.pick(&self.ready);
+                    let proc = self.ready.remove(index);
+                    self.dispatch(proc);
+                    self.start_slice(self.dispatch_timeslice().get());
+                    crate::SchedulingDecision::Run {
+                        pid: self.running_process.as_ref().unwrap().pid(),
+                        timeslice: NonZeroUsize::new(self.remaining_running_time).unwrap(),
+                    }
+                } else {
+                    if !self.wait.is_empty() || !self.suspended.is_empty() {
+                        // Wait queue is not empty, check for panic
+                        if self.init {
+                            self.init = false;
+                            self.idle_reason = Some(IdleReason::InitExited);
+                            return crate::SchedulingDecision::Panic;
+                        }
+                        // Check for deadlock (there are no sleeping processes, the processes in the wait
+                        // queue wait for a signal or for a child that can't run)
+                        if self.sleepers.is_empty() && self.suspended.is_empty() {
+                            self.idle_reason = Some(IdleReason::Deadlock);
+                            return crate::SchedulingDecision::Deadlock;
+                        } else if self.sleepers.is_empty() {
+                            // Wait for a suspended process to be resumed
+                            self.sleep = 1;
+                            self.idle_reason = Some(IdleReason::Suspended);
+                            return crate::SchedulingDecision::Idle(NonZeroUsize::MIN);
+                        } else {
+                            // Sleep the processor for a minimum amount of time until some process wakes up
+                            let Reverse((wake_tick, _, pid)) = self.sleepers.pop().unwrap();
+                            let min_amount = wake_tick - self.current_tick;
+                            // Find it in the wait queue and remove it, then push it to the ready queue
+                            let index = self.wait.iter().position(|proc| proc.pid == pid).unwrap();
+                            // Save the minimum amount to update all timings in the next next
+                            let mut proc = self.wait.remove(index);
+                            // It leaves the wait queue early, account its I/O wait now
+                            if let Some(latency) = proc.io_latency.take() {
+                                self.device_wait_ticks += latency;
+                            }
+                            // A throttled process is held back by the scheduler, not by itself
+                            let throttled = matches!(proc.state, ProcessState::Throttled { .. });
+                            if !throttled {
+                                self.policy.woken(&mut proc);
+                            }
+                            proc.state = ProcessState::Ready;
+                            self.push_ready(proc);
+                            self.sleep = min_amount;
+                            let min_amount = NonZeroUsize::new(min_amount).unwrap();
+                            if throttled {
+                                self.idle_reason = Some(IdleReason::Throttled);
+                                return crate::SchedulingDecision::Idle(min_amount);
+                            }
+                            self.idle_reason = Some(IdleReason::AllSleeping);
+                            // Sleep the processor for a minimum amount of time
+                            return crate::SchedulingDecision::Sleep(min_amount);
+                        }
+                    }
+                    // Handle the case when there's no process available to run
+                    self.idle_reason = Some(IdleReason::NoProcesses);
+                    self.done()
+                }
+            }
+        }
+    }
+    /// Make a process taken from the ready queue the running process.
+    fn dispatch(&mut self, mut proc: ProcessInfo) {
+        self.context_switch(&mut proc);
+        proc.state = ProcessState::Running;
+        proc.first_run.get_or_insert(self.current_tick);
+        self.policy.dispatched(&mut proc);
+        self.fork_pending(&mut proc);
+        self.running_process = Some(proc);
+    }
+    /// Returns `true` if the process with the given pid is running or ready.
+    fn can_run(&self, pid: Pid) -> bool {
+        self.running_process
+            .iter()
+            .chain(self.ready.iter())
+            .any(|proc| proc.pid == pid)
+    }
+    /// Pass a decision through the decision filter and update the queues
+    /// to match the decision that it returns.
+    fn filter_decision(
+        &mut self,
+        decision: crate::SchedulingDecision,
+    ) -> crate::SchedulingDecision {
+        let Some(filter) = self.filter.as_mut() else {
+            return decision;
+        };
+        let filtered = match filter(decision) {
+            // A process that can't run is replaced by the head of the ready queue
+            crate::SchedulingDecision::Run { pid, timeslice } if !self.can_run(pid) => {
+                match self.running_process.iter().chain(self.ready.iter()).next() {
+                    Some(head) => crate::SchedulingDecision::Run {
+                        pid: head.pid,
+                        timeslice,
+                    },
+                    None => decision,
+                }
+            }
+            filtered => filtered,
+        };
+        if filtered == decision {
+            return decision;
+        }
+        // The process chosen by the scheduler goes back to the front of the ready queue
+        let preempted = match filtered {
+            crate::SchedulingDecision::Run { pid, .. } => {
+                self.running_process.take_if(|proc| proc.pid != pid)
+            }
+            _ => self.running_process.take(),
+        };
+        if let Some(mut proc) = preempted {
+            proc.state = ProcessState::Ready;
+            self.push_ready_front(proc);
+        }
+        match filtered {
+            crate::SchedulingDecision::Run { pid, timeslice } => {
+                if self.running_process.is_none() {
+                    // Dispatch the process chosen by the filter
+                    let Some(index) = self.ready.iter().position(|proc| proc.pid == pid) else {
+                        unreachable!("the decision filter runs process {} that is not ready", pid);
+                    };
+                    let proc = self.ready.remove(index);
+                    self.dispatch(proc);
+                }
+                self.remaining_running_time = timeslice.into();
+                self.slice_left = 0;
+                self.sleep = 0;
+                self.idle_reason = None;
+            }
+            crate::SchedulingDecision::Sleep(amount) | crate::SchedulingDecision::Idle(amount) => {
+                // The time passes in the next call to next
+                self.sleep = amount.into();
+                self.idle_reason = Some(IdleReason::AllSleeping);
+            }
+            _ => self.sleep = 0,
+        }
+        filtered
+    }
+}
+
+impl Process for ProcessInfo {
+    fn pid(&self) -> crate::Pid {
+        self.pid
+    }
+    fn parent(&self) -> Option<Pid> {
+        self.parent
+    }
+    fn state(&self) -> ProcessState {
+        self.state
+    }
+    fn timings(&self) -> (usize, usize, usize) {
+        self.timings
+    }
+    fn priority(&self) -> i8 {
+        self.effective_priority()
+    }
+    fn extra(&self) -> String {
+        self.meta.to_string()
+    }
+    fn voluntary_switches(&self) -> usize {
+        self.voluntary_switches
+    }
+    fn involuntary_switches(&self) -> usize {
+        self.involuntary_switches
+    }
+}
+
+impl<P: SelectionPolicy> Scheduler for GenericScheduler<P> {
+    fn next(&mut self) -> crate::SchedulingDecision {
+        let decision = self.decide();
+        let decision = self.filter_decision(decision);
+        log::debug!("tick {}: {}", self.current_tick, decision);
+        decision
+    }
+
+    fn stop(&mut self, _reason: crate::StopReason) -> crate::SyscallResult {
+        if let Some(proc) = &self.running_process {
+            log::trace!(
+                "tick {}: process {} stopped with {:?}",
+                self.current_tick,
+                proc.pid,
+                _reason
+            );
+        }
+        // Charge the time needed to handle the system call
+        if matches!(_reason, crate::StopReason::Syscall { .. }) {
+            self.charge_syscall();
+        }
+        match _reason {
+            crate::StopReason::Syscall { syscall, remaining } => {
+                // The time used by the running process, including the system call
+                let elapsed = self.elapsed(remaining);
+                match syscall {
+                    Syscall::Fork(priority) => {
+                        // Increase all total timings (no time passes for the fork of the first process)
+                        if self.running_process.is_some() {
+                            self.increase_timings(elapsed);
+                        }
+                        // Generate a new process
+                        let new_pid = self.generate_pid();
+                        // Keep the priority of the new process within the range of the policy
+                        let priority = self
+                            .policy
+                            .clamp_priority(self.fork_priority(new_pid, priority));
+                        let mut new_process =
+                            ProcessInfo::new(new_pid, priority, self.current_tick);
+                        // The running process is the parent (none for the init process)
+                        new_process.parent = self.running_process.as_ref().map(|proc| proc.pid);
+                        if let Some(parent) = &self.running_process {
+                            new_process.quota = self.child_quota(parent);
+                            new_process.meta = parent.meta.clone();
+                        }
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process
+                            self.account_syscall(&mut running_process, elapsed);
+                            // Save the remaining time for the running process and regain ownership
+                            if self.child_runs_first && !running_process.preempt_disabled {
+                                // The parent is preempted, it runs again right after the child
+                                running_process.state = ProcessState::Ready;
+                                running_process.involuntary_switches += 1;
+                                self.record_burst(&mut running_process);
+                                self.push_ready_front(running_process);
+                                self.remaining_running_time = self.timeslice.into();
+                            } else {
+                                self.remaining_running_time = remaining;
+                                self.running_process = Some(running_process);
+                            }
+                        }
+                        // Add it to the ready queue, before its parent if the parent was preempted
+                        if new_process.parent.is_some() && self.running_process.is_none() {
+                            self.push_ready_front(new_process);
+                        } else {
+                            self.push_ready(new_process);
+                        }
+                        // Return the pid of the just created process
+                        SyscallResult::Pid(new_pid)
+                    }
+                    Syscall::ForkDeferred(priority) => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // The child is created when the process is dispatched again
+                            running_process.pending_forks.push(priority);
+                            // Update the timings of the running process and the remaining time
+                            self.account_syscall(&mut running_process, elapsed);
+                            self.remaining_running_time = remaining;
+                            self.running_process = Some(running_process);
+                        }
+                        SyscallResult::Success
+                    }
+                    Syscall::Sleep(amount) => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and push it to the wait queue
+                            running_process.state = ProcessState::Waiting { event: None };
+                            running_process.io_latency = None;
+                            self.account_syscall(&mut running_process, elapsed);
+                            running_process.voluntary_switches += 1;
+                            self.record_burst(&mut running_process);
+                            if amount == 0 {
+                                // A zero length sleep only yields, the process is ready again
+                                running_process.state = ProcessState::Ready;
+                                self.push_ready(running_process);
+                            } else {
+                                // Push the sleep amount
+                                self.push_sleeper(running_process.pid, self.round_sleep(amount));
+                                self.end_donations(&mut running_process, false);
+                                self.push_wait(running_process);
+                            }
+                        }
+                        // Reset the running process
+                        self.remaining_running_time = self.timeslice.into();
+                        self.running_process = None;
+                        SyscallResult::Success
+                    }
+                    Syscall::BlockIo {
+                        device,
+                        base_latency,
+                    } => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and push it to the wait queue
+                            running_process.state = ProcessState::Waiting { event: None };
+                            self.account_syscall(&mut running_process, elapsed);
+                            running_process.voluntary_switches += 1;
+                            self.record_burst(&mut running_process);
+                            // The request completes after its base latency and the latency of the device
+                            let latency =
+                                base_latency + self.devices.get(&device).copied().unwrap_or(0);
+                            running_process.io_latency = Some(latency);
+                            self.push_sleeper(running_process.pid, latency);
+                            self.end_donations(&mut running_process, false);
+                            self.push_wait(running_process);
+                        }
+                        // Reset the running process
+                        self.remaining_running_time = self.timeslice.into();
+                        self.running_process = None;
+                        SyscallResult::Success
+                    }
+                    Syscall::Wait(e) => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if self.event_check {
+                            self.waited_events.insert(e);
+                        }
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and push it to the wait queue
+                            running_process.state = ProcessState::Waiting { event: (Some(e)) };
+                            self.account_syscall(&mut running_process, elapsed);
+                            running_process.voluntary_switches += 1;
+                            self.record_burst(&mut running_process);
+                            self.end_donations(&mut running_process, false);
+                            self.push_wait(running_process);
+                        }
+                        // Reset the running process
+                        self.remaining_running_time = self.timeslice.into();
+                        self.running_process = None;
+                        SyscallResult::Success
+                    }
+                    Syscall::WaitAny(events) => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if self.event_check {
+                            self.waited_events.extend(events.iter());
+                        }
+                        let Some(mut running_process) = self.running_process.take() else {
+                            return SyscallResult::Success;
+                        };
+                        // Update the timings of the running process
+                        self.account_syscall(&mut running_process, elapsed);
+                        match events.first() {
+                            // Wait in the wait queue until one of the events is signaled
+                            Some(first) => {
+                                running_process.state =
+                                    ProcessState::Waiting { event: Some(first) };
+                                running_process.events = events;
+                                running_process.voluntary_switches += 1;
+                                self.record_burst(&mut running_process);
+                                self.end_donations(&mut running_process, false);
+                                self.push_wait(running_process);
+                                // Reset the running process
+                                self.remaining_running_time = self.timeslice.into();
+                                SyscallResult::Success
+                            }
+                            // There are no events to wait for, the process keeps running
+                            None => {
+                                self.remaining_running_time = remaining;
+                                self.running_process = Some(running_process);
+                                SyscallResult::Success
+                            }
+                        }
+                    }
+                    Syscall::Signal(e) => {
+                        // Increase all timings (no time passes if no process is running)
+                        if self.running_process.is_some() {
+                            self.increase_timings(elapsed);
+                        }
+                        if self.event_check {
+                            self.signaled_events.insert(e);
+                        }
+                        // Awaken all the processes that wait for the 'e' event, in a single pass
+                        // that keeps the relative order of both the awakened and the waiting ones
+                        let (awakened, waiting): (Vec<_>, Vec<_>) =
+                            self.wait.drain(..).partition(|proc| proc.waits_for(e));
+                        self.wait = waiting;
+                        // Mark them as Ready and push them to the ready queue
+                        for mut proc in awakened {
+                            proc.wake(e);
+                            log::trace!(
+                                "tick {}: process {} woke up by event {}",
+                                self.current_tick,
+                                proc.pid,
+                                e
+                            );
+                            self.policy.woken(&mut proc);
+                            self.push_ready(proc);
+                        }
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and the remaining time
+                            self.account_syscall(&mut running_process, elapsed);
+                            self.remaining_running_time = remaining;
+                            self.running_process = Some(running_process);
+                        }
+                        SyscallResult::Success
+                    }
+                    Syscall::SignalOne(e) => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if self.event_check {
+                            self.signaled_events.insert(e);
+                        }
+                        // Awaken the process that waits for the 'e' event chosen by the wake policy
+                        if let Some(index) = self.pick_waiter(e) {
+                            let mut proc = self.wait.remove(index);
+                            proc.wake(e);
+                            log::trace!(
+                                "tick {}: process {} woke up by event {}",
+                                self.current_tick,
+                                proc.pid,
+                                e
+                            );
+                            self.policy.woken(&mut proc);
+                            self.push_ready(proc);
+                        }
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and the remaining time
+                            self.account_syscall(&mut running_process, elapsed);
+                            self.remaining_running_time = remaining;
+                            self.running_process = Some(running_process);
+                        }
+                        SyscallResult::Success
+                    }
+                    Syscall::PreemptDisable | Syscall::PreemptEnable => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            running_process.preempt_disabled = syscall == Syscall::PreemptDisable;
+                            // Update the timings of the running process and the remaining time
+                            self.account_syscall(&mut running_process, elapsed);
+                            self.remaining_running_time = remaining;
+                            self.running_process = Some(running_process);
+                        }
+                        SyscallResult::Success
+                    }
+                    Syscall::Donate { to, amount } => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and the remaining time
+                            self.account_syscall(&mut running_process, elapsed);
+                            let donor = running_process.pid;
+                            let alive = self
+                                .ready
+                                .iter()
+                                .chain(self.wait.iter())
+                                .chain(self.suspended.iter())
+                                .any(|proc| proc.pid == to);
+                            if alive {
+                                // A new donation to the same process replaces the previous one
+                                let previous =
+                                    match self.donations.iter().position(|&(from, recipient, _)| {
+                                        from == donor && recipient == to
+                                    }) {
+                                        Some(index) => self.donations.remove(index).2,
+                                        None => 0,
+                                    };
+                                let amount = amount.max(0);
+                                if amount > 0 {
+                                    self.donations.push((donor, to, amount));
+                                }
+                                let change = i16::from(amount) - i16::from(previous);
+                                running_process.donation -= change;
+                                self.change_donation(to, change);
+                            }
+                            self.remaining_running_time = remaining;
+                            self.running_process = Some(running_process);
+                        }
+                        SyscallResult::Success
+                    }
+                    Syscall::Exit => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Its last burst and the exit are part of its timings
+                            self.account_syscall(&mut running_process, elapsed);
+                            running_process.voluntary_switches += 1;
+                            self.record_burst(&mut running_process);
+                            self.release(running_process);
+                        }
+                        // Reset running process
+                        self.remaining_running_time = self.timeslice.into();
+                        self.running_process = None;
+                        SyscallResult::Success
+                    }
+                    Syscall::Join(child) => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        let Some(mut running_process) = self.running_process.take() else {
+                            return SyscallResult::Success;
+                        };
+                        // Update the timings of the running process
+                        self.account_syscall(&mut running_process, elapsed);
+                        let pid = running_process.pid;
+                        let alive = self
+                            .ready
+                            .iter()
+                            .chain(self.wait.iter())
+                            .chain(self.suspended.iter())
+                            .any(|proc| proc.pid == child && proc.parent == Some(pid));
+                        if alive {
+                            // Wait in the wait queue until the child exits
+                            running_process.state = ProcessState::Waiting { event: None };
+                            running_process.joining = Some(child);
+                            running_process.voluntary_switches += 1;
+                            self.record_burst(&mut running_process);
+                            self.end_donations(&mut running_process, false);
+                            self.push_wait(running_process);
+                            // Reset the running process
+                            self.remaining_running_time = self.timeslice.into();
+                            return SyscallResult::Success;
+                        }
+                        // The process keeps running
+                        self.remaining_running_time = remaining;
+                        self.running_process = Some(running_process);
+                        match self
+                            .zombies
+                            .iter()
+                            .position(|zombie| zombie.pid == child && zombie.parent == Some(pid))
+                        {
+                            // Reap the child
+                            Some(index) => SyscallResult::Joined(self.reap_at(index).timings),
+                            // The process has no such child
+                            None => SyscallResult::Success,
+                        }
+                    }
+                    Syscall::AcquireLock => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        let Some(mut running_process) = self.running_process.take() else {
+                            return SyscallResult::Success;
+                        };
+                        // Update the timings of the running process
+                        self.account_syscall(&mut running_process, elapsed);
+                        match self.lock_holder {
+                            Some(holder) if holder != running_process.pid => {
+                                // Wait in the wait queue until the lock is handed over
+                                running_process.state = ProcessState::Waiting { event: None };
+                                running_process.locking = true;
+                                running_process.voluntary_switches += 1;
+                                self.record_burst(&mut running_process);
+                                self.end_donations(&mut running_process, false);
+                                self.push_wait(running_process);
+                                // Reset the running process
+                                self.remaining_running_time = self.timeslice.into();
+                            }
+                            _ => {
+                                // The process holds the lock and keeps running
+                                self.lock_holder = Some(running_process.pid);
+                                self.remaining_running_time = remaining;
+                                self.running_process = Some(running_process);
+                            }
+                        }
+                        SyscallResult::Success
+                    }
+                    Syscall::ReleaseLock => {
+                        // Increase all timings
+                        self.increase_timings(elapsed);
+                        if let Some(mut running_process) = self.running_process.take() {
+                            // Update the timings of the running process and the remaining time
+                            self.account_syscall(&mut running_process, elapsed);
+                            let pid = running_process.pid;
+                            self.remaining_running_time = remaining;
+                            self.running_process = Some(running_process);
+                            self.release_lock(pid);
+                        }
+                        SyscallResult::Success
+                    }
+                }
+            }
+            crate::StopReason::Expired => {
+                // Increase all timings
+                self.increase_timings(self.remaining_running_time);
+                if let Some(mut running_process) = self.running_process.take() {
+                    // Update the timings
+                    running_process.timings.0 += self.remaining_running_time;
+                    running_process.burst += self.remaining_running_time;
+                    running_process.timings.2 += self.remaining_running_time;
+                    if self.slice_left > 0 {
+                        // Only an interval of the timeslice ended
+                        if running_process.preempt_disabled
+                            || !self.policy.preempts(&running_process, &self.ready)
+                        {
+                            // It keeps running for the next interval
+                            self.running_process = Some(running_process);
+                            self.next_interval();
+                            return SyscallResult::Success;
+                        }
+                        // The policy runs another process first, then this one
+                        running_process.state = ProcessState::Ready;
+                        running_process.involuntary_switches += 1;
+                        self.record_burst(&mut running_process);
+                        self.push_ready_front(running_process);
+                    } else if running_process
+                        .execution_limit
+                        .is_some_and(|limit| running_process.timings.2 >= limit.get())
+                    {
+                        // The process used its whole execution time, it is terminated
+                        self.timed_out.push(running_process.pid);
+                        self.release(running_process);
+                    } else if let Some(amount) = self.throttle_amount(&running_process) {
+                        // The process used its whole quota, it waits for the next period
+                        running_process.involuntary_switches += 1;
+                        self.record_burst(&mut running_process);
+                        self.throttle(running_process, amount);
+                    } else if running_process.preempt_disabled {
+                        // The process can't be preempted, it keeps running for a new quanta
+                        self.fork_pending(&mut running_process);
+                        self.running_process = Some(running_process);
+                        self.start_slice(self.dispatch_timeslice().get());
+                        return SyscallResult::Success;
+                    } else if running_process.burst < self.min_run {
+                        // The process did not run for its minimum time yet, it keeps running
+                        let left = self.min_run - running_process.burst;
+                        let timeslice = self.process_timeslice(&running_process);
+                        self.fork_pending(&mut running_process);
+                        self.running_process = Some(running_process);
+                        self.start_slice(left.min(timeslice.get()));
+                        return SyscallResult::Success;
+                    } else {
+                        self.policy.expired(&mut running_process);
+                        if self.alone_expiry == AloneExpiry::Continue && self.ready.is_empty() {
+                            // No other process is ready, it keeps running for a new quanta
+                            self.fork_pending(&mut running_process);
+                            self.running_process = Some(running_process);
+                            self.start_slice(self.dispatch_timeslice().get());
+                            return SyscallResult::Success;
+                        }
+                        if self.alone_expiry == AloneExpiry::Renew && self.ready.is_empty() {
+                            // No other process is ready, it keeps running for a new quanta,
+                            // but it is counted as if it was dispatched again
+                            running_process.involuntary_switches += 1;
+                            self.record_burst(&mut running_process);
+                            self.fork_pending(&mut running_process);
+                            self.running_process = Some(running_process);
+                            self.start_slice(self.dispatch_timeslice().get());
+                            return SyscallResult::Success;
+                        }
+                        // Change its state and push it to the ready queue
+                        running_process.state = ProcessState::Ready;
+                        running_process.involuntary_switches += 1;
+                        self.record_burst(&mut running_process);
+                        self.push_ready(running_process);
+                    }
+                }
+                // Reset the running process
+                self.running_process = None;
+                self.remaining_running_time = self.timeslice.into();
+                SyscallResult::Success
+            }
+        }
+    }
+
+    fn list(&mut self) -> Vec<&dyn Process> {
+        self.settle_queues();
+        self.processes()
+    }
+
+    fn ready_list(&mut self) -> Vec<&dyn Process> {
+        self.settle_queues();
+        self.ready.iter().map(|proc| proc as &dyn Process).collect()
+    }
+
+    fn waiting_list(&mut self) -> Vec<&dyn Process> {
+        self.settle_queues();
+        self.wait.iter().map(|proc| proc as &dyn Process).collect()
+    }
+
+    fn take_wake_result(&mut self, pid: Pid) -> Option<SyscallResult> {
+        self.ready
+            .iter_mut()
+            .chain(self.wait.iter_mut())
+            .chain(self.running_process.iter_mut())
+            .find(|proc| proc.pid == pid)?
+            .wake_result
+            .take()
+    }
+
+    fn remaining_timeslice(&self) -> Option<NonZeroUsize> {
+        self.running_process
+            .as_ref()
+            .and_then(|_| NonZeroUsize::new(self.remaining_running_time))
+    }
+
+    fn describe(&self) -> String {
+        // The name of the policy, without its module path and its suffix
+        let policy = std::any::type_name::<P>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        format!(
+            "{}(timeslice={}, min_remaining={})",
+            policy.strip_suffix("Policy").unwrap_or(policy),
+            self.timeslice,
+            self.minimum_remaining_timeslice
+        )
+    }
+
+    fn is_idle(&mut self) -> bool {
+        self.running_process.is_none() && self.ready.is_empty()
+    }
+
+    fn validate_invariants(&mut self) -> Result<(), String> {
+        let mut processes = self.processes();
+        processes.extend(self.zombies());
+        check_unique_pids(&processes)?;
+        // Every sleeping process has exactly one sleeper
+        let sleepers: HashSet<Pid> = self
+            .sleepers
+            .iter()
+            .map(|Reverse((_, _, pid))| *pid)
+            .collect();
+        if sleepers.len() != self.sleepers.len() {
+            return Err(String::from("a process has more than one sleeper"));
+        }
+        let mut sleeping = 0;
+        for proc in &self.wait {
+            // A throttled process sleeps until the next quota period
+            let is_sleeping = matches!(
+                proc.state,
+                ProcessState::Waiting { event: None } | ProcessState::Throttled { .. }
+            ) && proc.joining.is_none()
+                && !proc.locking;
+            if is_sleeping != sleepers.contains(&proc.pid) {
+                return Err(format!(
+                    "the process {} is in the {} state and it has {} sleeper",
+                    proc.pid,
+                    proc.state,
+                    if is_sleeping { "no" } else { "a" }
+                ));
+            }
+            sleeping += usize::from(is_sleeping);
+        }
+        if sleeping != sleepers.len() {
+            return Err(String::from("a sleeper is not in the wait queue"));
+        }
+        // The processes wait for the lock only while another process holds it
+        match self.lock_holder {
+            Some(holder) if !processes.iter().any(|proc| proc.pid() == holder) => {
+                return Err(format!("the lock is held by the missing process {holder}"));
+            }
+            Some(holder)
+                if self
+                    .wait
+                    .iter()
+                    .any(|proc| proc.locking && proc.pid == holder) =>
+            {
+                return Err(format!("the process {holder} waits for its own lock"));
+            }
+            None if self.wait.iter().any(|proc| proc.locking) => {
+                return Err(String::from("a process waits for a lock that is not held"));
+            }
+            _ => {}
+        }
+        if let Some(proc) = self
+            .suspended
+            .iter()
+            .find(|proc| proc.state != ProcessState::Suspended)
+        {
+            return Err(format!(
+                "the suspended process {} is in the {} state",
+                proc.pid, proc.state
+            ));
+        }
+        // A target latency or the own timeslice of the process can give longer timeslices
+        let timeslice = self
+            .target_latency
+            .map_or(self.timeslice, |target_latency| {
+                target_latency.max(self.min_slice).max(self.timeslice)
+            })
+            .max(
+                self.running_process
+                    .as_ref()
+                    .map_or(NonZeroUsize::MIN, |proc| self.process_timeslice(proc)),
+            );
+        check_running(
+            self.running_process
+                .as_ref()
+                .map(|proc| proc as &dyn Process),
+            self.remaining_running_time,
+            timeslice.get(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RoundRobin, SchedulingDecision, StopReason};
+
+    fn round_robin(timeslice: usize, minimum_remaining_timeslice: usize) -> RoundRobin {
+        RoundRobin::new(
+            NonZeroUsize::new(timeslice).unwrap(),
+            minimum_remaining_timeslice,
+        )
+    }
+
+    fn syscall<P: SelectionPolicy>(
+        scheduler: &mut GenericScheduler<P>,
+        syscall: Syscall,
+        remaining: usize,
+    ) -> SyscallResult {
+        scheduler.stop(StopReason::Syscall { syscall, remaining })
+    }
+
+    fn runs(decision: SchedulingDecision, expected: usize) -> bool {
+        matches!(decision, SchedulingDecision::Run { pid, .. } if pid == expected)
+    }
+
+    fn timings(scheduler: &mut RoundRobin, pid: usize) -> (usize, usize, usize) {
+        scheduler
+            .list()
+            .into_iter()
+            .find(|proc| proc.pid() == pid)
+            .unwrap()
+            .timings()
+    }
+
+    #[test]
+    fn queued_time_is_counted_once() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { .. }));
+        // The child waits in the ready queue from tick 2
+        assert_eq!(
+            syscall(&mut scheduler, Syscall::Fork(0), 3),
+            SyscallResult::Pid(Pid::new(2))
+        );
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(timings(&mut scheduler, 2), (3, 0, 0));
+        assert_eq!(timings(&mut scheduler, 2), (3, 0, 0));
+        // The time is settled again when the child is dispatched
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+        assert_eq!(timings(&mut scheduler, 2), (3, 0, 0));
+        assert_eq!(scheduler.timings_scaled(Pid::new(1)), Some((5, 1, 4)));
+    }
+
+    #[test]
+    fn an_interrupt_charges_the_handler_and_rejects_a_longer_remaining_time() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert!(scheduler.interrupt(5).is_err());
+        assert_eq!(timings(&mut scheduler, 1), (1, 1, 0));
+        // The last of the two time units is taken by the interrupt handler
+        assert_eq!(scheduler.interrupt(2), Ok(SyscallResult::Success));
+        assert_eq!(timings(&mut scheduler, 1), (3, 2, 1));
+        assert_eq!(scheduler.interrupts(), 1);
+        assert!(runs(scheduler.next(), 2));
+    }
+
+    #[test]
+    fn a_deferred_child_is_created_when_the_parent_gets_a_new_quanta() {
+        let mut scheduler = round_robin(5, 1).with_alone_expiry(AloneExpiry::Continue);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(runs(scheduler.next(), 1));
+        syscall(&mut scheduler, Syscall::ForkDeferred(0), 3);
+        assert_eq!(scheduler.list().len(), 1);
+        // The parent is alone, it keeps running for a new quanta and its child is created
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(scheduler.ready_list().len(), 1);
+        assert!(runs(scheduler.next(), 1));
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+    }
+
+    #[test]
+    fn a_process_never_runs_longer_than_its_quota_in_a_period() {
+        let mut scheduler = round_robin(5, 1).with_quota_period(NonZeroUsize::new(20).unwrap());
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(runs(scheduler.next(), 1));
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+        // The quota is already used, the process is throttled instead of dispatched
+        assert!(scheduler.set_quota(Pid::new(1), NonZeroUsize::new(2).unwrap()));
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+        let throttled = scheduler.list().into_iter().find(|proc| proc.pid() == 1);
+        assert!(matches!(
+            throttled.unwrap().state(),
+            ProcessState::Throttled { until: 20 }
+        ));
+        scheduler.stop(StopReason::Expired);
+        // It does not run again in the first period, and for 2 time units in the next ones
+        let mut used = [0; 5];
+        while scheduler.current_tick() < 100 {
+            let period = scheduler.current_tick() / 20;
+            let before = timings(&mut scheduler, 1).2;
+            if let SchedulingDecision::Run { .. } = scheduler.next() {
+                scheduler.stop(StopReason::Expired);
+            }
+            used[period] += timings(&mut scheduler, 1).2 - before;
+        }
+        assert_eq!(used, [0, 2, 2, 2, 2]);
+    }
+
+    /// Runs the most recently ready process first and sums the bursts.
+    #[derive(Default)]
+    struct LastFirst(usize);
+
+    impl SelectionPolicy for LastFirst {
+        fn pick(&mut self, ready: &[ProcessInfo]) -> usize {
+            ready.len() - 1
+        }
+
+        fn burst_completed(&mut self, _proc: &ProcessInfo, burst: usize) {
+            self.0 += burst;
+        }
+    }
+
+    #[test]
+    fn the_policy_picks_the_next_process() {
+        let mut scheduler = GenericScheduler::<LastFirst>::new(NonZeroUsize::new(5).unwrap(), 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        syscall(&mut scheduler, Syscall::Fork(0), 3);
+        scheduler.stop(StopReason::Expired);
+        // The init process is the last one that became ready
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 1));
+        syscall(&mut scheduler, Syscall::Sleep(10), 2);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 3));
+        assert_eq!(scheduler.policy().0, 5 + 3);
+        assert_eq!(
+            scheduler.describe(),
+            "LastFirst(timeslice=5, min_remaining=1)"
+        );
+        assert_eq!(
+            round_robin(5, 1).describe(),
+            "RoundRobin(timeslice=5, min_remaining=1)"
+        );
+    }
+
+    #[test]
+    fn wait_any_reports_the_event_once_woken_up() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        let waiting = syscall(&mut scheduler, Syscall::WaitAny(EventSet::new(&[3, 7])), 3);
+        assert_eq!(waiting, SyscallResult::Success);
+        assert_eq!(
+            scheduler.block_reason(Pid::new(1)),
+            Some(BlockReason::AnyEvent(EventSet::new(&[3, 7])))
+        );
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+        syscall(&mut scheduler, Syscall::Signal(7), 4);
+        assert_eq!(scheduler.take_wake_result(Pid::new(2)), None);
+        assert_eq!(
+            scheduler.take_wake_result(Pid::new(1)),
+            Some(SyscallResult::Event(7))
+        );
+        assert_eq!(scheduler.take_wake_result(Pid::new(1)), None);
+    }
+
+    #[test]
+    fn join_reports_the_timings_of_the_child_once_it_exits() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        let joining = syscall(&mut scheduler, Syscall::Join(Pid::new(2)), 3);
+        assert_eq!(joining, SyscallResult::Success);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+        syscall(&mut scheduler, Syscall::Exit, 3);
+        // The child is reaped by the parent, after it waited and ran for two
+        // time units, the last one being the exit
+        assert!(scheduler.zombies().is_empty());
+        assert_eq!(
+            scheduler.take_wake_result(Pid::new(1)),
+            Some(SyscallResult::Joined((3, 1, 1)))
+        );
+    }
+
+    /// Runs a child that exits before its parent joins it.
+    fn exit_child_first(scheduler: &mut RoundRobin) {
+        syscall(scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(scheduler, Syscall::Fork(0), 4);
+        scheduler.stop(StopReason::Expired);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+        syscall(scheduler, Syscall::Exit, 3);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 1));
+    }
+
+    #[test]
+    fn an_exited_child_is_only_kept_as_a_zombie_if_requested() {
+        let mut scheduler = round_robin(5, 1);
+        exit_child_first(&mut scheduler);
+        assert!(scheduler.zombies().is_empty());
+        let joined = syscall(&mut scheduler, Syscall::Join(Pid::new(2)), 4);
+        assert_eq!(joined, SyscallResult::Success);
+
+        let mut scheduler = round_robin(5, 1).with_zombies(true);
+        exit_child_first(&mut scheduler);
+        assert_eq!(scheduler.zombies().len(), 1);
+        let joined = syscall(&mut scheduler, Syscall::Join(Pid::new(2)), 4);
+        assert_eq!(joined, SyscallResult::Joined((6, 1, 1)));
+        assert!(scheduler.zombies().is_empty());
+    }
+
+    /// Forks children that exit right away and returns their pids.
+    fn fork_and_exit(scheduler: &mut RoundRobin, children: usize) -> Vec<Pid> {
+        syscall(scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        (0..children)
+            .map(|_| {
+                let SyscallResult::Pid(child) = syscall(scheduler, Syscall::Fork(0), 4) else {
+                    panic!("the fork failed");
+                };
+                scheduler.stop(StopReason::Expired);
+                assert!(
+                    matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == child)
+                );
+                syscall(scheduler, Syscall::Exit, 4);
+                assert!(
+                    matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 1)
+                );
+                child
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pids_are_only_reused_if_requested() {
+        let pids = fork_and_exit(&mut round_robin(5, 1), 100);
+        assert_eq!(pids, (2..102).map(Pid::new).collect::<Vec<_>>());
+
+        let pids = fork_and_exit(&mut round_robin(5, 1).with_pid_reuse(true), 100);
+        assert!(pids.iter().all(|&pid| pid == 2));
+    }
+
+    #[test]
+    fn a_zombie_keeps_its_pid_until_it_is_reaped() {
+        let mut scheduler = round_robin(5, 1).with_zombies(true).with_pid_reuse(true);
+        let pids = fork_and_exit(&mut scheduler, 3);
+        assert_eq!(pids, [Pid::new(2), Pid::new(3), Pid::new(4)]);
+        assert!(scheduler.reap(Pid::new(3)).is_some());
+        assert_eq!(
+            syscall(&mut scheduler, Syscall::Fork(0), 4),
+            SyscallResult::Pid(Pid::new(3))
+        );
+    }
+
+    #[test]
+    fn a_filter_that_runs_a_missing_process_falls_back_to_the_head_of_the_queue() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        let mut decisions = 0;
+        scheduler.set_decision_filter(move |decision| {
+            decisions += 1;
+            match decisions {
+                1 => SchedulingDecision::Run {
+                    pid: Pid::new(99),
+                    timeslice: NonZeroUsize::new(3).unwrap(),
+                },
+                _ => decision,
+            }
+        });
+        // The clone counts its own decisions
+        let mut clone = scheduler.clone();
+        for scheduler in [&mut scheduler, &mut clone] {
+            assert_eq!(
+                scheduler.next(),
+                SchedulingDecision::Run {
+                    pid: Pid::new(1),
+                    timeslice: NonZeroUsize::new(3).unwrap(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn a_process_woken_up_while_the_processor_sleeps_is_ready() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Sleep(5), 9);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Sleep(NonZeroUsize::new(5).unwrap())
+        );
+        let ready = scheduler.ready_list();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].state(), ProcessState::Ready);
+    }
+
+    #[test]
+    fn a_suspended_child_can_be_joined_and_receive_donations() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+        assert!(scheduler.suspend(Pid::new(2)));
+        assert!(runs(scheduler.next(), 1));
+        let donate = Syscall::Donate {
+            to: Pid::new(2),
+            amount: 3,
+        };
+        syscall(&mut scheduler, donate, 9);
+        let child = scheduler.list().into_iter().find(|proc| proc.pid() == 2);
+        assert_eq!(child.unwrap().priority(), 3);
+        // The parent waits for its suspended child
+        syscall(&mut scheduler, Syscall::Join(Pid::new(2)), 8);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Idle(NonZeroUsize::MIN)
+        );
+        assert!(scheduler.resume(Pid::new(2)));
+        assert!(runs(scheduler.next(), 2));
+        syscall(&mut scheduler, Syscall::Exit, 9);
+        assert!(matches!(
+            scheduler.take_wake_result(Pid::new(1)),
+            Some(SyscallResult::Joined(_))
+        ));
+    }
+
+    #[test]
+    fn the_jitter_of_the_elapsed_time_stays_within_its_bound() {
+        for tick_jitter in [0, 2] {
+            let mut scheduler = round_robin(10, 1).with_tick_jitter(tick_jitter);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            let mut charged = HashSet::new();
+            for _ in 0..20 {
+                assert!(runs(scheduler.next(), 1));
+                // The process used 2 time units and 1 for the system call
+                let before = timings(&mut scheduler, 1).0;
+                syscall(&mut scheduler, Syscall::Signal(0), 7);
+                charged.insert(timings(&mut scheduler, 1).0 - before);
+                scheduler.stop(StopReason::Expired);
+            }
+            if tick_jitter == 0 {
+                assert_eq!(charged, HashSet::from([3]));
+            } else {
+                assert!(charged.len() > 1);
+                assert!(charged.iter().all(|elapsed| (1..=5).contains(elapsed)));
+            }
+        }
+    }
+
+    #[test]
+    fn signal_one_wakes_up_exactly_one_of_the_waiters() {
+        for (wake_policy, woken) in [
+            (WakePolicy::LongestWaiting, 2),
+            (WakePolicy::HighestPriority, 3),
+        ] {
+            let mut scheduler = round_robin(10, 1).with_wake_policy(wake_policy);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            scheduler.next();
+            for (priority, remaining) in [(0, 9), (3, 8), (1, 7)] {
+                syscall(&mut scheduler, Syscall::Fork(priority), remaining);
+            }
+            scheduler.stop(StopReason::Expired);
+            for pid in [2, 3, 4] {
+                assert!(runs(scheduler.next(), pid));
+                syscall(&mut scheduler, Syscall::Wait(5), 9);
+            }
+            assert!(runs(scheduler.next(), 1));
+            syscall(&mut scheduler, Syscall::SignalOne(5), 9);
+            let ready = scheduler.ready_list();
+            assert_eq!(ready.len(), 1);
+            assert_eq!(ready[0].pid(), woken);
+            assert_eq!(scheduler.waiting_list().len(), 2);
+        }
+    }
+
+    #[test]
+    fn a_process_keeps_the_processor_while_its_preemption_is_disabled() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(runs(scheduler.next(), 1));
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        syscall(&mut scheduler, Syscall::PreemptDisable, 3);
+        // It holds the lock across the expiry, with a new quanta
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Run {
+                pid: Pid::new(1),
+                timeslice: NonZeroUsize::new(5).unwrap(),
+            }
+        );
+        syscall(&mut scheduler, Syscall::PreemptEnable, 2);
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+    }
+
+    #[test]
+    fn a_killed_process_is_removed_from_its_queue() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        for remaining in [4, 3, 2] {
+            syscall(&mut scheduler, Syscall::Fork(0), remaining);
+        }
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 3));
+        syscall(&mut scheduler, Syscall::Sleep(50), 4);
+        assert!(runs(scheduler.next(), 4));
+        // A waiting, the running and a ready process
+        for pid in [3, 4, 2] {
+            assert!(scheduler.kill(Pid::new(pid)));
+            assert!(!scheduler.kill(Pid::new(pid)));
+        }
+        assert_eq!(scheduler.remaining_timeslice(), None);
+        assert!(scheduler.waiting_list().is_empty());
+        assert_eq!(scheduler.ready_list().len(), 1);
+        assert!(runs(scheduler.next(), 1));
+        assert!(scheduler.kill(Pid::new(1)));
+        assert!(scheduler.list().is_empty());
+    }
+
+    #[test]
+    fn killing_the_init_process_panics_the_system() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert!(scheduler.kill(Pid::new(1)));
+        assert_eq!(scheduler.next(), SchedulingDecision::Panic);
+    }
+
+    #[test]
+    fn a_cloned_scheduler_diverges_from_the_original() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        let mut branch = scheduler.clone();
+        syscall(&mut scheduler, Syscall::Sleep(10), 3);
+        syscall(&mut branch, Syscall::Fork(0), 3);
+        assert_eq!(scheduler.ready_list().len(), 1);
+        assert_eq!(scheduler.waiting_list().len(), 1);
+        assert_eq!(branch.ready_list().len(), 2);
+        assert!(branch.waiting_list().is_empty());
+        // The branch keeps its own running process and clock
+        assert!(runs(scheduler.next(), 2));
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(branch.remaining_timeslice(), NonZeroUsize::new(3));
+        assert_eq!(branch.current_tick(), 2);
+        assert_eq!(scheduler.current_tick(), 7);
+    }
+
+    #[test]
+    fn more_ready_processes_shorten_the_timeslice_down_to_the_minimum() {
+        let mut scheduler = round_robin(10, 1).with_target_latency(
+            NonZeroUsize::new(12).unwrap(),
+            NonZeroUsize::new(3).unwrap(),
+        );
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        let timeslice = |decision| match decision {
+            SchedulingDecision::Run { timeslice, .. } => timeslice.get(),
+            decision => panic!("unexpected decision {decision:?}"),
+        };
+        let mut current = timeslice(scheduler.next());
+        assert_eq!(current, 12);
+        // Every running process forks a child before its timeslice expires
+        for expected in [6, 4, 3, 3] {
+            syscall(&mut scheduler, Syscall::Fork(0), current - 1);
+            scheduler.stop(StopReason::Expired);
+            current = timeslice(scheduler.next());
+            assert_eq!(current, expected);
+        }
+    }
+
+    #[test]
+    fn a_zero_length_sleep_only_yields_the_processor() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Sleep(0), 4);
+        assert!(scheduler.waiting_list().is_empty());
+        assert_eq!(scheduler.ready_list()[0].state(), ProcessState::Ready);
+        assert!(runs(scheduler.next(), 1));
+        // With another ready process, it goes behind it
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        syscall(&mut scheduler, Syscall::Sleep(0), 3);
+        assert!(runs(scheduler.next(), 2));
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 1));
+    }
+
+    #[test]
+    fn the_tick_limit_stops_processes_that_signal_each_other_forever() {
+        let mut scheduler = round_robin(10, 1).with_max_ticks(1000);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        syscall(&mut scheduler, Syscall::Wait(1), 8);
+        // Each process wakes up the other one, then waits to be woken up
+        let decision = loop {
+            match scheduler.next() {
+                SchedulingDecision::Run { pid, .. } => {
+                    let (own, other) = if pid == 1 { (1, 2) } else { (2, 1) };
+                    syscall(&mut scheduler, Syscall::Signal(other), 9);
+                    syscall(&mut scheduler, Syscall::Wait(own), 8);
+                }
+                decision => break decision,
+            }
+        };
+        assert_eq!(decision, SchedulingDecision::Done);
+        assert_eq!(scheduler.current_tick(), 1000);
+        assert_eq!(scheduler.last_idle_reason(), Some(IdleReason::TickLimit));
+    }
+
+    #[test]
+    fn a_stranded_waiter_is_reported_instead_of_done_if_strict() {
+        for (strict_done, expected) in [
+            (false, SchedulingDecision::Done),
+            (true, SchedulingDecision::Stranded(Pid::new(1))),
+        ] {
+            let mut scheduler = round_robin(5, 1)
+                .with_max_ticks(10)
+                .with_strict_done(strict_done);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            scheduler.next();
+            syscall(&mut scheduler, Syscall::Fork(0), 4);
+            // Nothing can signal the event that process 1 waits for
+            syscall(&mut scheduler, Syscall::Wait(3), 3);
+            assert!(scheduler.suspend(Pid::new(2)));
+            let decision = loop {
+                match scheduler.next() {
+                    SchedulingDecision::Idle(_) => {}
+                    decision => break decision,
+                }
+            };
+            assert_eq!(decision, expected);
+        }
+    }
+
+    #[test]
+    fn a_cpu_bound_process_only_has_involuntary_switches() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        scheduler.stop(StopReason::Expired);
+        for _ in 0..3 {
+            assert!(runs(scheduler.next(), 2));
+            scheduler.stop(StopReason::Expired);
+            assert!(runs(scheduler.next(), 1));
+            syscall(&mut scheduler, Syscall::Sleep(1), 4);
+        }
+        let switches = |scheduler: &mut RoundRobin, pid| {
+            let list = scheduler.list();
+            let proc = list.into_iter().find(|proc| proc.pid() == pid).unwrap();
+            (proc.voluntary_switches(), proc.involuntary_switches())
+        };
+        assert_eq!(switches(&mut scheduler, 2), (0, 3));
+        assert_eq!(switches(&mut scheduler, 1), (3, 1));
+    }
+
+    #[test]
+    fn the_peak_of_the_ready_queue_is_reached_after_a_fork_storm() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        for remaining in (5..10).rev() {
+            syscall(&mut scheduler, Syscall::Fork(0), remaining);
+        }
+        assert_eq!(scheduler.max_ready_depth(), (5, 5));
+        // The parent joins its five children in the ready queue
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(scheduler.max_ready_depth(), (6, 10));
+        for pid in 2..=6 {
+            assert!(runs(scheduler.next(), pid));
+            syscall(&mut scheduler, Syscall::Exit, 9);
+        }
+        assert_eq!(scheduler.max_ready_depth(), (6, 10));
+    }
+
+    #[test]
+    fn a_forked_child_runs_before_its_parent_only_if_requested() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert_eq!(scheduler.remaining_timeslice(), NonZeroUsize::new(4));
+        assert!(runs(scheduler.next(), 1));
+
+        let mut scheduler = round_robin(5, 1).with_child_runs_first(true);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert_eq!(scheduler.remaining_timeslice(), None);
+        assert!(runs(scheduler.next(), 2));
+        // The child is followed by its parent, ahead of the processes that were ready
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert!(runs(scheduler.next(), 3));
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 1));
+    }
+
+    #[test]
+    fn a_sustained_busy_period_drives_the_recent_utilization_to_one() {
+        let mut scheduler =
+            round_robin(5, 1).with_utilization_window(NonZeroUsize::new(10).unwrap());
+        assert_eq!(scheduler.recent_utilization(), 0.0);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Sleep(20), 4);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Sleep(_)));
+        assert!(runs(scheduler.next(), 1));
+        assert_eq!(scheduler.recent_utilization(), 0.0);
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(scheduler.recent_utilization(), 0.5);
+        assert!(runs(scheduler.next(), 1));
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(scheduler.recent_utilization(), 1.0);
+    }
+
+    #[test]
+    fn a_restored_process_finishes_its_original_quanta() {
+        let mut scheduler = round_robin(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 3);
+        let (proc, remaining) = scheduler.checkpoint_running().unwrap();
+        assert_eq!(remaining, 3);
+        assert!(runs(scheduler.next(), 2));
+        // It moves to another scheduler, with the rest of its timeslice
+        let mut other = round_robin(5, 1);
+        other.restore_running(proc, remaining);
+        assert_eq!(
+            other.next(),
+            SchedulingDecision::Run {
+                pid: Pid::new(1),
+                timeslice: NonZeroUsize::new(3).unwrap(),
+            }
+        );
+        other.stop(StopReason::Expired);
+        assert_eq!(timings(&mut other, 1), (5, 1, 4));
+    }
+
+    #[test]
+    fn a_process_alone_keeps_a_single_burst_only_without_reset() {
+        for (no_reset_when_alone, bursts, involuntary_switches) in [
+            (false, vec![(1..2, 1), (4..8, 2)], 2),
+            (true, vec![(8..16, 1)], 0),
+        ] {
+            let mut scheduler = round_robin(5, 1).with_no_reset_when_alone(no_reset_when_alone);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            for _ in 0..2 {
+                assert!(runs(scheduler.next(), 1));
+                scheduler.stop(StopReason::Expired);
+            }
+            assert!(runs(scheduler.next(), 1));
+            syscall(&mut scheduler, Syscall::Sleep(10), 4);
+            // The timings are the same, only the bursts and the switches differ
+            assert_eq!(timings(&mut scheduler, 1), (11, 1, 10));
+            let histogram: Vec<_> = scheduler
+                .burst_histogram()
+                .into_iter()
+                .filter(|&(_, count)| count > 0)
+                .collect();
+            assert_eq!(histogram, bursts);
+            let list = scheduler.list();
+            assert_eq!(list[0].involuntary_switches(), involuntary_switches);
+        }
+    }
+
+    #[test]
+    fn a_scheduler_with_init_runs_it_without_a_fork() {
+        let mut scheduler = round_robin(5, 1).with_init(3);
+        assert_eq!(scheduler.list()[0].priority(), 3);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Run {
+                pid: Pid::new(1),
+                timeslice: NonZeroUsize::new(5).unwrap(),
+            }
+        );
+        // The next fork creates the second process
+        assert_eq!(
+            syscall(&mut scheduler, Syscall::Fork(0), 4),
+            SyscallResult::Pid(Pid::new(2))
+        );
+    }
+
+    #[test]
+    fn a_process_runs_for_its_minimum_time_before_it_is_preempted() {
+        let mut scheduler = round_robin(2, 1).with_min_run(5);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(runs(scheduler.next(), 1));
+        syscall(&mut scheduler, Syscall::Fork(0), 1);
+        // It keeps running in timeslices that stop at its minimum time
+        for timeslice in [2, 1] {
+            scheduler.stop(StopReason::Expired);
+            assert_eq!(
+                scheduler.next(),
+                SchedulingDecision::Run {
+                    pid: Pid::new(1),
+                    timeslice: NonZeroUsize::new(timeslice).unwrap(),
+                }
+            );
+        }
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(timings(&mut scheduler, 1), (5, 1, 4));
+        assert!(runs(scheduler.next(), 2));
+    }
+
+    #[test]
+    fn a_process_is_terminated_once_it_reaches_its_execution_limit() {
+        let mut scheduler = round_robin(8, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 7);
+        assert!(scheduler.set_execution_limit(Pid::new(2), NonZeroUsize::new(20).unwrap()));
+        scheduler.stop(StopReason::Expired);
+        let mut timeslices = Vec::new();
+        while scheduler.timed_out().is_empty() {
+            if let SchedulingDecision::Run { pid, timeslice } = scheduler.next() {
+                if pid == 2 {
+                    timeslices.push(timeslice.get());
+                }
+            }
+            scheduler.stop(StopReason::Expired);
+        }
+        // The last timeslice is shortened to the rest of the limit
+        assert_eq!(timeslices, [8, 8, 4]);
+        assert_eq!(scheduler.timed_out(), [Pid::new(2)]);
+        assert_eq!(scheduler.list().len(), 1);
+    }
+
+    #[test]
+    fn the_own_timeslice_of_a_process_is_used_from_its_next_dispatch() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        assert!(scheduler.set_timeslice(Pid::new(1), NonZeroUsize::new(3).unwrap()));
+        assert!(scheduler.set_timeslice(Pid::new(2), NonZeroUsize::new(4).unwrap()));
+        assert!(!scheduler.set_timeslice(Pid::new(3), NonZeroUsize::new(4).unwrap()));
+        // The running process keeps its current timeslice
+        assert_eq!(scheduler.remaining_timeslice(), NonZeroUsize::new(9));
+        scheduler.stop(StopReason::Expired);
+        for (pid, timeslice) in [(2, 4), (1, 3)] {
+            assert_eq!(
+                scheduler.next(),
+                SchedulingDecision::Run {
+                    pid: Pid::new(pid),
+                    timeslice: NonZeroUsize::new(timeslice).unwrap(),
+                }
+            );
+            scheduler.stop(StopReason::Expired);
+        }
+    }
+
+    #[test]
+    fn a_signal_between_bursts_wakes_up_the_waiters_without_using_time() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        syscall(&mut scheduler, Syscall::Wait(1), 8);
+        assert_eq!(scheduler.remaining_timeslice(), None);
+        // No process is running, as if the signal came from an interrupt
+        assert_eq!(
+            syscall(&mut scheduler, Syscall::Signal(1), 0),
+            SyscallResult::Success
+        );
+        assert!(scheduler.waiting_list().is_empty());
+        assert_eq!(scheduler.ready_list().len(), 2);
+        assert_eq!(scheduler.current_tick(), 2);
+        assert_eq!(timings(&mut scheduler, 1), (2, 2, 0));
+        assert!(runs(scheduler.next(), 2));
+    }
+
+    #[test]
+    fn a_sleep_is_rounded_up_to_the_sleep_granularity() {
+        let mut scheduler = round_robin(10, 1).with_sleep_granularity(4);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        // The process sleeps at tick 1 and wakes up 4 time units later
+        syscall(&mut scheduler, Syscall::Sleep(3), 9);
+        assert_eq!(
+            scheduler.block_reason(Pid::new(1)),
+            Some(BlockReason::Sleeping { remaining: 4 })
+        );
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Sleep(NonZeroUsize::new(4).unwrap())
+        );
+        assert!(runs(scheduler.next(), 1));
+        assert_eq!(scheduler.current_tick(), 5);
+    }
+
+    #[test]
+    fn the_spawned_and_exited_processes_are_counted() {
+        let mut scheduler = round_robin(10, 1).with_pid_reuse(true);
+        assert_eq!(
+            (scheduler.total_spawned(), scheduler.total_exited()),
+            (0, 0)
+        );
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        for remaining in [9, 8, 7] {
+            syscall(&mut scheduler, Syscall::Fork(0), remaining);
+        }
+        scheduler.stop(StopReason::Expired);
+        assert!(runs(scheduler.next(), 2));
+        syscall(&mut scheduler, Syscall::Exit, 9);
+        assert!(scheduler.kill(Pid::new(3)));
+        assert_eq!(
+            (scheduler.total_spawned(), scheduler.total_exited()),
+            (4, 2)
+        );
+        // A reused pid is still a new process
+        assert!(runs(scheduler.next(), 4));
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        assert_eq!(
+            (scheduler.total_spawned(), scheduler.total_exited()),
+            (5, 2)
+        );
+        assert_eq!(scheduler.list().len(), 3);
+    }
+
+    #[test]
+    fn the_block_reason_tells_a_sleeper_from_an_event_waiter() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        syscall(&mut scheduler, Syscall::Fork(0), 8);
+        syscall(&mut scheduler, Syscall::Join(Pid::new(2)), 7);
+        assert!(runs(scheduler.next(), 2));
+        syscall(&mut scheduler, Syscall::Sleep(6), 9);
+        assert!(runs(scheduler.next(), 3));
+        syscall(&mut scheduler, Syscall::Wait(4), 7);
+        // Both are in the wait queue, but only one of them has a wake time
+        assert_eq!(
+            scheduler.block_reason(Pid::new(2)),
+            Some(BlockReason::Sleeping { remaining: 3 })
+        );
+        assert_eq!(
+            scheduler.block_reason(Pid::new(3)),
+            Some(BlockReason::Event(4))
+        );
+        assert_eq!(
+            scheduler.block_reason(Pid::new(1)),
+            Some(BlockReason::Join(Pid::new(2)))
+        );
+        assert_eq!(scheduler.block_reason(Pid::new(4)), None);
+        syscall(&mut scheduler, Syscall::Signal(4), 0);
+        assert_eq!(scheduler.block_reason(Pid::new(3)), None);
+    }
+
+    #[test]
+    fn the_kernel_lock_has_a_single_holder() {
+        let mut scheduler = round_robin(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        syscall(&mut scheduler, Syscall::Fork(0), 8);
+        syscall(&mut scheduler, Syscall::AcquireLock, 7);
+        scheduler.stop(StopReason::Expired);
+        for pid in [2, 3] {
+            assert!(runs(scheduler.next(), pid));
+            syscall(&mut scheduler, Syscall::AcquireLock, 9);
+            assert_eq!(scheduler.lock_holder(), Some(Pid::new(1)));
+            assert_eq!(
+                scheduler.block_reason(Pid::new(pid)),
+                Some(BlockReason::Lock)
+            );
+        }
+        // Every release hands the lock over to the longest waiter only
+        for (holder, next) in [(1, Some(2)), (2, Some(3)), (3, None)] {
+            assert!(runs(scheduler.next(), holder));
+            syscall(&mut scheduler, Syscall::ReleaseLock, 9);
+            assert_eq!(scheduler.lock_holder(), next.map(Pid::new));
+            assert_eq!(scheduler.ready_list().len(), usize::from(next.is_some()));
+            syscall(&mut scheduler, Syscall::Sleep(100), 8);
+        }
+    }
+
+    #[test]
+    fn the_throughput_counts_the_exited_processes_per_time_unit() {
+        let mut scheduler = round_robin(20, 1).with_zombies(true);
+        assert_eq!(scheduler.throughput(), 0.0);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        for remaining in (15..20).rev() {
+            syscall(&mut scheduler, Syscall::Fork(0), remaining);
+        }
+        syscall(&mut scheduler, Syscall::Wait(9), 14);
+        // The five children exit after 94 more time units
+        for (pid, remaining) in [(2, 1), (3, 1), (4, 1), (5, 1), (6, 2)] {
+            assert!(runs(scheduler.next(), pid));
+            syscall(&mut scheduler, Syscall::Exit, remaining);
+        }
+        assert_eq!(scheduler.current_tick(), 100);
+        // The zombies that were not reaped yet are completed
+        assert_eq!(scheduler.zombies().len(), 5);
+        assert_eq!(scheduler.throughput(), 0.05);
+    }
+
+    #[test]
+    fn the_processor_is_idle_instead_of_sleeping_for_a_throttled_process() {
+        let mut scheduler = round_robin(5, 1).with_quota_period(NonZeroUsize::new(10).unwrap());
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        assert!(scheduler.set_quota(Pid::new(1), NonZeroUsize::new(2).unwrap()));
+        assert!(runs(scheduler.next(), 1));
+        scheduler.stop(StopReason::Expired);
+        // The scheduler holds the process back until the next period
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Idle(NonZeroUsize::new(8).unwrap())
+        );
+        assert_eq!(scheduler.last_idle_reason(), Some(IdleReason::Throttled));
+        assert!(runs(scheduler.next(), 1));
+        // The process holds itself back
+        syscall(&mut scheduler, Syscall::Sleep(3), 1);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Sleep(NonZeroUsize::new(3).unwrap())
+        );
+        assert_eq!(scheduler.last_idle_reason(), Some(IdleReason::AllSleeping));
+    }
+
+    #[test]
+    fn the_alone_expiry_decides_the_switches_and_the_dispatch_latency() {
+        for (alone_expiry, involuntary_switches, dispatch_ticks, bursts) in [
+            (AloneExpiry::Requeue, 3, 4, 3),
+            (AloneExpiry::Renew, 3, 1, 3),
+            (AloneExpiry::Continue, 0, 1, 0),
+        ] {
+            let mut scheduler = round_robin(5, 1)
+                .with_alone_expiry(alone_expiry)
+                .with_dispatch_latency(1);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            for _ in 0..3 {
+                assert!(runs(scheduler.next(), 1));
+                scheduler.stop(StopReason::Expired);
+            }
+            assert!(runs(scheduler.next(), 1));
+            let list = scheduler.list();
+            assert_eq!(list[0].involuntary_switches(), involuntary_switches);
+            assert_eq!(scheduler.dispatch_ticks(), dispatch_ticks);
+            let recorded: usize = scheduler
+                .burst_histogram()
+                .iter()
+                .map(|(_, count)| count)
+                .sum();
+            assert_eq!(recorded, bursts);
+        }
+    }
+
+    #[test]
+    fn the_next_wakeup_is_the_nearest_wake_tick() {
+        let mut scheduler = round_robin(10, 1);
+        assert_eq!(scheduler.next_wakeup(), None);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        // Process 1 wakes up at tick 22
+        syscall(&mut scheduler, Syscall::Sleep(20), 8);
+        assert_eq!(scheduler.next_wakeup(), Some(22));
+        assert!(runs(scheduler.next(), 2));
+        // Process 2 wakes up at tick 10, before process 1
+        syscall(&mut scheduler, Syscall::Sleep(5), 7);
+        assert_eq!(scheduler.next_wakeup(), Some(10));
+        // It does not advance the time
+        assert_eq!(scheduler.current_tick(), 5);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Sleep(NonZeroUsize::new(5).unwrap())
+        );
+        assert_eq!(scheduler.next_wakeup(), Some(22));
+    }
+
+    #[test]
+    fn sleepers_wake_up_in_the_order_of_their_wake_ticks() {
+        let mut scheduler = round_robin(10, 1);
+        let sleeps = |decision, amount| {
+            decision == SchedulingDecision::Sleep(NonZeroUsize::new(amount).unwrap())
+        };
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 9);
+        syscall(&mut scheduler, Syscall::Fork(0), 8);
+        // Process 1 wakes up at tick 13, processes 2 and 3 at tick 8
+        syscall(&mut scheduler, Syscall::Sleep(10), 7);
+        assert!(runs(scheduler.next(), 2));
+        syscall(&mut scheduler, Syscall::Sleep(4), 9);
+        assert!(runs(scheduler.next(), 3));
+        syscall(&mut scheduler, Syscall::Sleep(3), 9);
+        assert!(sleeps(scheduler.next(), 3));
+        // The processes that wake up at the same tick keep the order in which they slept
+        for pid in [2, 3] {
+            assert!(runs(scheduler.next(), pid));
+            syscall(&mut scheduler, Syscall::Sleep(20), 9);
+        }
+        assert!(sleeps(scheduler.next(), 3));
+        assert!(runs(scheduler.next(), 1));
+        syscall(&mut scheduler, Syscall::Sleep(20), 9);
+        // Process 2 wakes up first, at tick 29
+        assert!(sleeps(scheduler.next(), 15));
+        assert!(runs(scheduler.next(), 2));
+    }
+}
@@ -14,7 +14,22 @@ mod empty;
 pub use empty::Empty;
 
 mod round_robin;
-pub use round_robin::RoundRobin;
+pub use round_robin::{RoundRobin, RoundRobinPolicy};
 
 mod round_robin_priority;
-pub use round_robin_priority::RoundRobinPriority;
+pub use round_robin_priority::{RoundRobinPriority, RoundRobinPriorityPolicy, SecondaryKey};
+
+mod adaptive_round_robin;
+pub use adaptive_round_robin::AdaptiveRoundRobin;
+
+mod decay_round_robin;
+pub use decay_round_robin::DecayRoundRobin;
+
+mod nice_round_robin;
+pub use nice_round_robin::{nice_to_weight, NiceRoundRobin};
+
+mod generic_scheduler;
+pub use generic_scheduler::{GenericScheduler, LevelStat, ProcessInfo, SelectionPolicy};
+
+mod cooperative;
+pub use cooperative::Cooperative;
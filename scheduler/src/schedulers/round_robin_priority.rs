@@ -1,396 +1,425 @@
-use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
 
-use crate::{Pid, Process, ProcessState, Scheduler, Syscall, SyscallResult};
+use crate::{GenericScheduler, Process, ProcessInfo, SelectionPolicy};
 
-pub struct ProcessInfo {
-    pid: Pid,
-    state: ProcessState,
-    timings: (usize, usize, usize),
-    priority: i8,
-    default_priority: i8,
-    _extra: String,
+/// A round robin scheduler that runs the processes with higher priorities first.
+///
+/// The priority of a process drops by one every time it uses its whole
+/// timeslice, and rises back by one, up to its default priority, every time
+/// it makes a system call.
+pub type RoundRobinPriority = GenericScheduler<RoundRobinPriorityPolicy>;
+
+/// The order of the ready processes that have the same priority.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum SecondaryKey {
+    /// The process that has been ready for the longest time runs first.
+    #[default]
+    Fifo,
+
+    /// The process that has used the least execution time runs first.
+    LeastCpu,
+
+    /// The process with the earliest [`crate::ProcessMeta::deadline`] runs first,
+    /// the processes without a deadline run last.
+    EarliestDeadline,
+}
+
+impl SecondaryKey {
+    fn rank(self, proc: &ProcessInfo) -> usize {
+        // The processes with the same rank keep the FIFO order
+        match self {
+            SecondaryKey::Fifo => 0,
+            SecondaryKey::LeastCpu => proc.timings().2,
+            SecondaryKey::EarliestDeadline => proc.meta().deadline.unwrap_or(usize::MAX),
+        }
+    }
 }
 
-pub struct RoundRobinPriority {
-    timeslice: NonZeroUsize,
-    minimum_remaining_timeslice: usize,
-    ready: Vec<ProcessInfo>,
-    wait: Vec<ProcessInfo>,
-    pid_counter: usize,
-    running_process: Option<ProcessInfo>,
-    remaining_running_time: usize,
-    init: bool,
-    sleep_amounts: Vec<usize>,
-    sleep: usize,
+/// A policy that runs the ready process with the highest priority, with the
+/// donations that it received or made, and the one that has been ready for
+/// the longest time among the processes with the same priority.
+///
+/// A process whose priority changes while it is ready goes behind the
+/// processes that were ready before the change.
+#[derive(Clone, Debug)]
+pub struct RoundRobinPriorityPolicy {
+    priority_range: RangeInclusive<i8>,
+    inverted: bool,
+    secondary_key: SecondaryKey,
+    wake_boost: i8,
 }
-impl RoundRobinPriority {
-    pub fn new(timeslice: NonZeroUsize, minimum_remaining_timeslice: usize) -> Self {
+
+impl Default for RoundRobinPriorityPolicy {
+    fn default() -> Self {
         Self {
-            timeslice,
-            minimum_remaining_timeslice,
-            ready: Vec::new(),
-            wait: Vec::new(),
-            pid_counter: 1,
-            running_process: None,
-            remaining_running_time: timeslice.into(),
-            init: false,
-            sleep_amounts: Vec::new(),
-            sleep: 0,
+            priority_range: i8::MIN..=i8::MAX,
+            inverted: false,
+            secondary_key: SecondaryKey::Fifo,
+            wake_boost: 0,
         }
     }
-    pub fn generate_pid(&mut self) -> Pid {
-        // Generate a new PID
-        let new_pid = Pid::new(self.pid_counter);
-        self.pid_counter += 1;
-        new_pid
-    }
-    pub fn increase_timings(&mut self, amount: usize) {
-        // Update timings for all processes and sleep amounts
-        for proc in &mut self.ready {
-            proc.timings.0 += amount;
-        }
-        for proc in &mut self.wait {
-            proc.timings.0 += amount;
+}
+
+impl RoundRobinPriorityPolicy {
+    fn level(&self, proc: &ProcessInfo) -> i16 {
+        // The processes from the lowest level run first
+        let priority = i16::from(proc.priority());
+        if self.inverted {
+            priority
+        } else {
+            -priority
         }
-        for sleep in &mut self.sleep_amounts {
-            // An usize can't be negative
-            if *sleep < amount {
-                *sleep = 0;
-            } else {
-                *sleep -= amount;
-            }
+    }
+}
+
+impl SelectionPolicy for RoundRobinPriorityPolicy {
+    fn pick(&mut self, ready: &[ProcessInfo]) -> usize {
+        // The first process with the highest priority (the lowest one if inverted)
+        // and the lowest secondary rank
+        ready
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, proc)| (self.level(proc), self.secondary_key.rank(proc)))
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn clamp_priority(&self, priority: i8) -> i8 {
+        priority.clamp(*self.priority_range.start(), *self.priority_range.end())
+    }
+
+    fn preempts(&mut self, running: &ProcessInfo, ready: &[ProcessInfo]) -> bool {
+        // Check if a ready process has a higher priority (a lower one if inverted)
+        let level = self.level(running);
+        ready.iter().any(|proc| self.level(proc) < level)
+    }
+
+    fn dispatched(&mut self, proc: &mut ProcessInfo) {
+        // The boost of a process that woke up lasts until it runs
+        if proc.base_priority() > proc.default_priority() {
+            proc.set_base_priority(proc.default_priority());
         }
-        // Take the awakened processes from the queue and make them ready
-        let mut zero_amount_indices = Vec::new();
-        let mut proc_amount_indices = Vec::new();
-        // Save the indices of the processes that have 0 amount to sleep
-        for (index, &amount) in self.sleep_amounts.iter().enumerate() {
-            if amount == 0 {
-                zero_amount_indices.push(index);
-            }
+    }
+
+    fn expired(&mut self, proc: &mut ProcessInfo) {
+        if proc.base_priority() > 0.max(*self.priority_range.start()) {
+            proc.set_base_priority(proc.base_priority() - 1);
         }
-        // Save the indexes of all sleeping processes from wait
-        for (wait_index, proc) in self.wait.iter().enumerate() {
-            if let ProcessState::Waiting { event } = &proc.state {
-                if Option::is_none(event) {
-                    proc_amount_indices.push(wait_index);
-                }
-            }
+    }
+
+    fn system_call(&mut self, proc: &mut ProcessInfo) {
+        // A process that gives up the processor gets back one priority level
+        if proc.base_priority() < proc.default_priority() {
+            proc.set_base_priority(proc.base_priority() + 1);
         }
+    }
 
-        // Remove the sleep(0) processes, and then update the new indexes
-        // (if you remove an element from a vec, the other indexes will be swapped with the index of the for loop)
-        for (iter, i) in zero_amount_indices.iter().enumerate() {
-            let new_index = i - iter;
-            if let Some(index) = proc_amount_indices.get(new_index).cloned() {
-                let mut proc = self.wait.remove(index);
-                self.sleep_amounts.remove(new_index);
-                proc.state = ProcessState::Ready;
-                self.ready.push(proc);
-            }
+    fn woken(&mut self, proc: &mut ProcessInfo) {
+        // The process runs ahead of the processes with the same default priority
+        if self.wake_boost > 0 {
+            let boosted = proc.default_priority().saturating_add(self.wake_boost);
+            proc.set_base_priority(self.clamp_priority(boosted));
         }
     }
 }
 
-impl Process for ProcessInfo {
-    fn pid(&self) -> crate::Pid {
-        self.pid
-    }
-    fn state(&self) -> ProcessState {
-        self.state
+impl RoundRobinPriority {
+    /// Keep the priorities of the processes within `priority_range`.
+    ///
+    /// The priorities of new processes are clamped to the range, and the
+    /// priority changes never leave it. The default range is `i8::MIN..=i8::MAX`.
+    ///
+    /// ## Panics
+    ///
+    /// If the range is empty.
+    pub fn with_priority_range(mut self, priority_range: RangeInclusive<i8>) -> Self {
+        assert!(!priority_range.is_empty(), "the priority range is empty");
+        self.policy_mut().priority_range = priority_range;
+        self
     }
-    fn timings(&self) -> (usize, usize, usize) {
-        self.timings
+    /// Run the processes with lower priorities first, to check how a
+    /// workload depends on the priority order.
+    ///
+    /// Only the order in which the ready processes are dispatched changes,
+    /// it is the highest priority first by default.
+    pub fn with_inverted_priorities(mut self, invert: bool) -> Self {
+        self.policy_mut().inverted = invert;
+        self
     }
-    fn priority(&self) -> i8 {
-        self.priority
+    /// Choose which of the ready processes with the same priority runs
+    /// first, instead of the one that has been ready for the longest time.
+    pub fn with_secondary_key(mut self, secondary_key: SecondaryKey) -> Self {
+        self.policy_mut().secondary_key = secondary_key;
+        self
     }
-    fn extra(&self) -> String {
-        String::new()
+    /// Raise the priority of the processes that wake up from a wait or a
+    /// sleep to their default priority plus `wake_boost`, within the
+    /// priority range.
+    ///
+    /// The boost lasts for one timeslice: once the process is dispatched,
+    /// its priority decays back to its default priority. The default boost
+    /// of 0 leaves the priorities unchanged.
+    pub fn with_wake_boost(mut self, wake_boost: i8) -> Self {
+        self.policy_mut().wake_boost = wake_boost;
+        self
     }
 }
 
-impl Scheduler for RoundRobinPriority {
-    fn next(&mut self) -> crate::SchedulingDecision {
-        // Increase all timings after a sleep (if 0, it will increase with 0)
-        self.increase_timings(self.sleep);
-        self.sleep = 0;
-
-        // Sort processes by priority in reverse order
-        self.ready.sort_by(|a, b| b.priority.cmp(&a.priority));
-        match self.running_process.take() {
-            Some(mut running_process) => {
-                // If there is a running process, check if it can be rescheduled
-                if self.remaining_running_time < self.minimum_remaining_timeslice {
-                    // Can't reschedule, mark it as ready and push it to the ready queue
-                    running_process.state = ProcessState::Ready;
-                    self.ready.push(running_process);
-                    // Sort processes by priority in reverse order
-                    self.ready.sort_by(|a, b| b.priority.cmp(&a.priority));
-                    // Get the first process from the ready queue and mark it as running
-                    let mut proc = self.ready.remove(0);
-                    proc.state = ProcessState::Running;
-                    self.running_process = Some(proc);
-                    self.remaining_running_time = self.timeslice.into();
-                    // Return its pid and timeslice
-                    return crate::SchedulingDecision::Run {
-                        pid: self.running_process.as_ref().unwrap().pid(),
-                        timeslice: NonZeroUsize::new(self.remaining_running_time).unwrap(),
-                    };
-                } else {
-                    // Regain ownership
-                    self.running_process = Some(running_process);
-                    // Reschedule the running process again
-                    return crate::SchedulingDecision::Run {
-                        pid: self.running_process.as_ref().unwrap().pid(),
-                        timeslice: NonZeroUsize::new(self.remaining_running_time).unwrap(),
-                    };
-                }
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::{Pid, Scheduler, SchedulingDecision, StopReason, Syscall, SyscallResult};
+
+    fn round_robin_priority(
+        timeslice: usize,
+        minimum_remaining_timeslice: usize,
+    ) -> RoundRobinPriority {
+        RoundRobinPriority::new(
+            NonZeroUsize::new(timeslice).unwrap(),
+            minimum_remaining_timeslice,
+        )
+    }
+
+    fn syscall(
+        scheduler: &mut RoundRobinPriority,
+        syscall: Syscall,
+        remaining: usize,
+    ) -> SyscallResult {
+        scheduler.stop(StopReason::Syscall { syscall, remaining })
+    }
+
+    fn process(scheduler: &mut RoundRobinPriority, pid: usize) -> ((usize, usize, usize), i8) {
+        let proc = scheduler
+            .list()
+            .into_iter()
+            .find(|proc| proc.pid() == pid)
+            .unwrap();
+        (proc.timings(), proc.priority())
+    }
+
+    #[test]
+    fn a_syscall_is_accounted_and_restores_the_priority() {
+        let mut scheduler = round_robin_priority(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(3), 0);
+        scheduler.next();
+        scheduler.stop(StopReason::Expired);
+        assert_eq!(process(&mut scheduler, 1), ((5, 0, 5), 2));
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 1));
+        // Two time units are used, the last one by the system call
+        syscall(&mut scheduler, Syscall::Fork(0), 3);
+        assert_eq!(process(&mut scheduler, 1), ((7, 1, 6), 3));
+    }
+
+    #[test]
+    fn an_interrupt_keeps_a_process_without_preemption_running() {
+        let mut scheduler =
+            round_robin_priority(10, 1).with_preempt_check_interval(NonZeroUsize::new(6).unwrap());
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::PreemptDisable, 5);
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert_eq!(scheduler.interrupt(1), Ok(SyscallResult::Success));
+        // The last of the three time units is taken by the interrupt handler
+        assert_eq!(process(&mut scheduler, 1), ((5, 3, 2), 0));
+        assert_eq!(scheduler.interrupts(), 0);
+        // The rest of the interval and of the timeslice
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Run {
+                pid: Pid::new(1),
+                timeslice: NonZeroUsize::new(1 + 4).unwrap(),
             }
-            None => {
-                // There is no running process
-                if !self.ready.is_empty() {
-                    // Check for panic (if the process with pid 1 has exited)
-                    if self.init {
-                        self.init = false;
-                        return crate::SchedulingDecision::Panic;
-                    }
-                    // Return the first process from the ready queue
-                    let mut proc = self.ready.remove(0);
-                    proc.state = ProcessState::Running;
-                    self.running_process = Some(proc);
-                    return crate::SchedulingDecision::Run {
-                        pid: self.running_process.as_ref().unwrap().pid(),
-                        timeslice: self.timeslice,
-                    };
-                } else {
-                    if !self.wait.is_empty() {
-                        // Both ready queue and wait queues are empty, check for panic
-                        if self.init {
-                            self.init = false;
-                            return crate::SchedulingDecision::Panic;
-                        }
-                        // Check for deadlock (there are only processes that wait for a signal in the wait queue)
-                        let mut is_deadlock = true;
-                        for proc in &self.wait {
-                            if let ProcessState::Waiting { event } = &proc.state {
-                                if Option::is_none(event) {
-                                    is_deadlock = false;
-                                    break;
-                                }
-                            }
-                        }
-                        if is_deadlock {
-                            return crate::SchedulingDecision::Deadlock;
-                        } else {
-                            // Sleep the processor for a minimum amount of time until some process wakes up
-                            let mut min_amount = std::usize::MAX;
-                            let mut min_index = 0;
-                            // Compute the minimum and get its index
-                            for (index, &amount) in self.sleep_amounts.iter().enumerate() {
-                                if amount < min_amount {
-                                    min_amount = amount;
-                                    min_index = index;
-                                }
-                            }
-                            // Remove its sleep amount
-                            self.sleep_amounts.remove(min_index);
-                            let mut wait_index = 0;
-                            let mut target_wait_index = 0;
-
-                            // Find it in the wait queue and remove it, then push it to the ready queue
-                            for (index, proc) in self.wait.iter().enumerate() {
-                                if let ProcessState::Waiting { event } = &proc.state {
-                                    if Option::is_none(event) {
-                                        if wait_index == min_index {
-                                            target_wait_index = index;
-                                            break;
-                                        }
-                                        wait_index += 1;
-                                    }
-                                }
-                            }
-                            // Save the minimum amount to update all timings in the next next
-                            let proc = self.wait.remove(target_wait_index);
-                            self.ready.push(proc);
-                            // Sort processes by priority in reverse order
-                            self.ready.sort_by(|a, b| b.priority.cmp(&a.priority));
-                            self.sleep = min_amount;
-                            return crate::SchedulingDecision::Sleep(
-                                // Sleep the processor for a minimum amount of time
-                                NonZeroUsize::new(min_amount).unwrap(),
-                            );
-                        }
-                    }
-                    // Handle the case when there's no process available to run
-                    crate::SchedulingDecision::Done
-                }
+        );
+    }
+
+    #[test]
+    fn an_interrupt_moves_the_running_process_to_the_ready_queue() {
+        let mut scheduler = round_robin_priority(5, 1);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 4);
+        assert_eq!(scheduler.interrupt(2), Ok(SyscallResult::Success));
+        assert_eq!(process(&mut scheduler, 1), ((3, 2, 1), 0));
+        assert_eq!(scheduler.interrupts(), 1);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+        assert_eq!(scheduler.interrupt(0), Ok(SyscallResult::Success));
+        assert_eq!(scheduler.interrupts(), 2);
+    }
+
+    #[test]
+    fn an_interrupt_past_the_interval_is_an_error() {
+        let mut scheduler = round_robin_priority(5, 1);
+        assert_eq!(scheduler.interrupt(0), Ok(SyscallResult::NoRunningProcess));
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(0), 3);
+        assert!(scheduler.interrupt(4).is_err());
+        // Nothing changed, the process still runs with the rest of its timeslice
+        assert_eq!(process(&mut scheduler, 1), ((2, 1, 1), 0));
+        assert_eq!(scheduler.interrupts(), 0);
+        assert_eq!(scheduler.remaining_timeslice(), NonZeroUsize::new(3));
+    }
+
+    #[test]
+    fn a_woken_process_runs_ahead_of_the_cpu_bound_ones_for_one_timeslice() {
+        for (wake_boost, expected) in [(0, 3), (2, 1)] {
+            let mut scheduler = round_robin_priority(5, 1).with_wake_boost(wake_boost);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            scheduler.next();
+            syscall(&mut scheduler, Syscall::Fork(0), 4);
+            syscall(&mut scheduler, Syscall::Fork(0), 3);
+            syscall(&mut scheduler, Syscall::Sleep(2), 2);
+            assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+            // Process 1 wakes up while process 2 runs, behind process 3
+            scheduler.stop(StopReason::Expired);
+            assert!(
+                matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == expected)
+            );
+        }
+        // The boost ends once the process is dispatched
+        let mut scheduler = round_robin_priority(5, 1).with_wake_boost(2);
+        syscall(&mut scheduler, Syscall::Fork(0), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Sleep(2), 4);
+        assert_eq!(process(&mut scheduler, 1).1, 0);
+        scheduler.next();
+        assert_eq!(process(&mut scheduler, 1).1, 2);
+        scheduler.next();
+        assert_eq!(process(&mut scheduler, 1).1, 0);
+    }
+
+    #[test]
+    fn the_priority_of_the_init_process_decides_the_first_schedule() {
+        for (init_priority, expected) in [(None, 1), (Some(1), 2)] {
+            let mut scheduler = round_robin_priority(5, 1);
+            if let Some(init_priority) = init_priority {
+                scheduler = scheduler.with_init_priority(init_priority);
             }
+            syscall(&mut scheduler, Syscall::Fork(5), 0);
+            assert_eq!(process(&mut scheduler, 1).1, init_priority.unwrap_or(5));
+            scheduler.next();
+            syscall(&mut scheduler, Syscall::Fork(2), 4);
+            scheduler.stop(StopReason::Expired);
+            assert!(
+                matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == expected)
+            );
         }
     }
 
-    fn stop(&mut self, _reason: crate::StopReason) -> crate::SyscallResult {
-        match _reason {
-            crate::StopReason::Syscall { syscall, remaining } => match syscall {
-                Syscall::Fork(priority) => {
-                    // Increase all total timings
-                    self.increase_timings(self.remaining_running_time - remaining);
-                    // Generate a new process
-                    let new_pid = self.generate_pid();
-                    let new_process = ProcessInfo {
-                        pid: new_pid,
-                        state: ProcessState::Ready,
-                        timings: (0, 0, 0),
-                        priority,
-                        default_priority: priority,
-                        _extra: String::new(),
-                    };
-                    // Add it to the ready queue
-                    self.ready.push(new_process);
-                    // Sort processes by priority in reverse order
-                    self.ready.sort_by(|a, b| b.priority.cmp(&a.priority));
-                    if let Some(mut running_process) = self.running_process.take() {
-                        if running_process.priority < running_process.default_priority {
-                            running_process.priority += 1;
-                        }
-                        // Update the timings of the running process
-                        running_process.timings.0 += self.remaining_running_time - remaining;
-                        running_process.timings.1 += 1;
-                        running_process.timings.2 += self.remaining_running_time - remaining - 1; // - 1 (the syscall)
-                                                                                                  // Save the remaining time for the running process and regain ownership
-                        self.remaining_running_time = remaining;
-                        self.running_process = Some(running_process);
-                    }
-                    // Return the pid of the just created process
-                    SyscallResult::Pid(new_pid)
-                }
-                Syscall::Sleep(amount) => {
-                    // Increase all timings
-                    self.increase_timings(self.remaining_running_time - remaining);
-                    if let Some(mut running_process) = self.running_process.take() {
-                        if running_process.priority < running_process.default_priority {
-                            running_process.priority += 1;
-                        }
-                        // Update the timings of the running process and push it to the wait queue
-                        running_process.state = ProcessState::Waiting { event: None };
-                        running_process.timings.0 += self.remaining_running_time - remaining;
-                        running_process.timings.1 += 1;
-                        running_process.timings.2 += self.remaining_running_time - remaining - 1; // - 1 (the syscall)
-                        self.wait.push(running_process);
-                        // Push the sleep amount
-                        self.sleep_amounts.push(amount);
-                    }
-                    // Reset the running process
-                    self.remaining_running_time = self.timeslice.into();
-                    self.running_process = None;
-                    SyscallResult::Success
-                }
-                Syscall::Wait(e) => {
-                    // Increase all timings
-                    self.increase_timings(self.remaining_running_time - remaining);
-                    if let Some(mut running_process) = self.running_process.take() {
-                        if running_process.priority < running_process.default_priority {
-                            running_process.priority += 1;
-                        }
-                        // Update the timings of the running process and push it to the wait queue
-                        running_process.state = ProcessState::Waiting { event: (Some(e)) };
-                        running_process.timings.0 += self.remaining_running_time - remaining;
-                        running_process.timings.1 += 1;
-                        running_process.timings.2 += self.remaining_running_time - remaining - 1; // - 1 (the syscall)
-                        self.wait.push(running_process);
-                    }
-                    // Reset the running process
-                    self.remaining_running_time = self.timeslice.into();
-                    self.running_process = None;
-                    SyscallResult::Success
-                }
-                Syscall::Signal(e) => {
-                    // Increase all timings
-                    self.increase_timings(self.remaining_running_time - remaining);
-                    // Awaken all the processes that wait for the 'e' event
-                    // First, save their indexes
-                    let mut procs_to_ready = Vec::new();
-                    for (index, proc) in self.wait.iter().enumerate() {
-                        if let ProcessState::Waiting { event } = &proc.state {
-                            if *event == Some(e) {
-                                procs_to_ready.push(index);
-                            }
-                        }
-                    }
-                    // Remove them from the wait queue, mark them as Ready and push to the ready queue
-                    for (index, i) in procs_to_ready.iter().enumerate() {
-                        // Keep track of the modified index when removing
-                        let modified_index = i - index;
-                        let mut new_proc = self.wait.remove(modified_index);
-                        new_proc.state = ProcessState::Ready;
-                        self.ready.push(new_proc);
-                        // Sort processes by priority in reverse order
-                        self.ready.sort_by(|a, b| b.priority.cmp(&a.priority));
-                    }
-                    if let Some(mut running_process) = self.running_process.take() {
-                        if running_process.priority < running_process.default_priority {
-                            running_process.priority += 1;
-                        }
-                        // Update the timings of the running process and the remaining time
-                        running_process.timings.0 += self.remaining_running_time - remaining;
-                        running_process.timings.1 += 1;
-                        running_process.timings.2 += self.remaining_running_time - remaining - 1; // - 1 (the syscall)
-                        self.remaining_running_time = remaining;
-                        self.running_process = Some(running_process);
-                    }
-                    SyscallResult::Success
-                }
-                Syscall::Exit => {
-                    // Increase all timings
-                    self.increase_timings(self.remaining_running_time - remaining);
-                    // Verify if process with pid 1 has exited
-                    if let Some(running_process) = self.running_process.take() {
-                        if running_process.pid == 1 {
-                            self.init = true;
-                        }
-                    }
-                    // Reset running process
-                    self.remaining_running_time = self.timeslice.into();
-                    self.running_process = None;
-                    SyscallResult::Success
-                }
-            },
-            crate::StopReason::Expired => {
-                // Increase all timings
-                self.increase_timings(self.remaining_running_time);
-                if let Some(mut running_process) = self.running_process.take() {
-                    if running_process.priority > 0 {
-                        running_process.priority -= 1;
-                    }
-                    // Change its state and update the timings
-                    running_process.state = ProcessState::Ready;
-                    running_process.timings.0 += self.remaining_running_time;
-                    running_process.timings.2 += self.remaining_running_time;
-                    // Push to the ready queue
-                    self.ready.push(running_process);
-                    // Sort processes by priority in reverse order
-                    self.ready.sort_by(|a, b| b.priority.cmp(&a.priority));
-                }
-                // Reset the running process
-                self.running_process = None;
-                self.remaining_running_time = self.timeslice.into();
-                SyscallResult::Success
+    #[test]
+    fn a_donation_lets_a_low_priority_process_run_first() {
+        let mut scheduler = round_robin_priority(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(5), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(1), 9);
+        syscall(&mut scheduler, Syscall::Fork(3), 8);
+        let donate = Syscall::Donate {
+            to: Pid::new(2),
+            amount: 4,
+        };
+        syscall(&mut scheduler, donate, 7);
+        assert_eq!(process(&mut scheduler, 1).1, 1);
+        assert_eq!(process(&mut scheduler, 2).1, 5);
+        syscall(&mut scheduler, Syscall::Sleep(100), 6);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+        // The donation ends once the recipient blocks
+        syscall(&mut scheduler, Syscall::Sleep(100), 9);
+        assert_eq!(process(&mut scheduler, 2).1, 1);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 3));
+    }
+
+    #[test]
+    fn a_higher_priority_arrival_preempts_at_the_next_interval() {
+        let mut scheduler =
+            round_robin_priority(20, 1).with_preempt_check_interval(NonZeroUsize::new(5).unwrap());
+        syscall(&mut scheduler, Syscall::Fork(5), 0);
+        scheduler.next();
+        syscall(&mut scheduler, Syscall::Fork(1), 4);
+        syscall(&mut scheduler, Syscall::Sleep(3), 3);
+        assert_eq!(
+            scheduler.next(),
+            SchedulingDecision::Run {
+                pid: Pid::new(2),
+                timeslice: NonZeroUsize::new(5).unwrap(),
             }
+        );
+        // Process 1 wakes up during the first interval and preempts process 2
+        scheduler.stop(StopReason::Expired);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 1));
+        syscall(&mut scheduler, Syscall::Sleep(100), 4);
+        assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 2));
+    }
+
+    #[test]
+    fn the_statistics_are_grouped_by_default_priority() {
+        let mut scheduler = round_robin_priority(4, 1);
+        syscall(&mut scheduler, Syscall::Fork(5), 0);
+        scheduler.next();
+        for (priority, remaining) in [(3, 3), (3, 2), (1, 1)] {
+            syscall(&mut scheduler, Syscall::Fork(priority), remaining);
+        }
+        scheduler.stop(StopReason::Expired);
+        for _ in 0..4 {
+            scheduler.next();
+            scheduler.stop(StopReason::Expired);
         }
+        let stats = scheduler.priority_stats();
+        let stat = |priority| {
+            let stat = stats[&priority];
+            (stat.processes, stat.execution_ticks, stat.wait_ticks)
+        };
+        // The init process ran until its priority decayed to the next level,
+        // the process with the lowest priority only waited
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stat(5), (1, 9, 8));
+        assert_eq!(stat(3), (2, 8, 29));
+        assert_eq!(stat(1), (1, 0, 17));
     }
 
-    fn list(&mut self) -> Vec<&dyn Process> {
-        // List all processes from my Scheduler
-        let mut list: Vec<&dyn Process> = Vec::new();
-        for i in &self.ready {
-            list.push(i)
+    #[test]
+    fn least_cpu_picks_the_least_run_process_of_a_level() {
+        for (secondary_key, expected) in [(SecondaryKey::Fifo, 2), (SecondaryKey::LeastCpu, 3)] {
+            let mut scheduler = round_robin_priority(10, 1).with_secondary_key(secondary_key);
+            syscall(&mut scheduler, Syscall::Fork(0), 0);
+            scheduler.next();
+            syscall(&mut scheduler, Syscall::Fork(0), 9);
+            syscall(&mut scheduler, Syscall::Fork(0), 8);
+            scheduler.stop(StopReason::Expired);
+            // Process 2 runs for 9 time units, process 3 for none, then both wait
+            for (pid, remaining) in [(2, 0), (3, 9)] {
+                assert!(
+                    matches!(scheduler.next(), SchedulingDecision::Run { pid: p, .. } if p == pid)
+                );
+                syscall(&mut scheduler, Syscall::Wait(1), remaining);
+            }
+            assert!(matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == 1));
+            syscall(&mut scheduler, Syscall::Signal(1), 9);
+            syscall(&mut scheduler, Syscall::Sleep(100), 8);
+            assert!(
+                matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == expected)
+            );
         }
-        for i in &self.wait {
-            list.push(i)
+    }
+
+    #[test]
+    fn a_ready_process_moves_to_the_level_of_its_new_priority() {
+        let mut scheduler = round_robin_priority(10, 1);
+        syscall(&mut scheduler, Syscall::Fork(2), 0);
+        scheduler.next();
+        for (priority, remaining) in [(1, 9), (1, 8), (0, 7)] {
+            syscall(&mut scheduler, Syscall::Fork(priority), remaining);
         }
-        if let Some(x) = &self.running_process {
-            list.push(x);
+        // Process 4 is moved above the others while it waits in the ready queue
+        assert!(scheduler.set_priority(Pid::new(4), 3));
+        // Process 2 stays at its level, but goes behind process 3
+        assert!(scheduler.set_priority(Pid::new(2), 1));
+        syscall(&mut scheduler, Syscall::Sleep(100), 6);
+        for expected in [4, 3, 2] {
+            assert!(
+                matches!(scheduler.next(), SchedulingDecision::Run { pid, .. } if pid == expected)
+            );
+            syscall(&mut scheduler, Syscall::Sleep(100), 9);
         }
-        list
     }
 }